@@ -0,0 +1,53 @@
+use std::collections::BTreeSet;
+
+use alloy_primitives::Address;
+use jsonrpsee::core::RpcResult;
+use jsonrpsee::proc_macros::rpc;
+
+/// The address lists consulted when admitting a transaction.
+///
+/// An allow-list set to `None` admits everyone; an allow-list set to an empty set admits no one.
+/// Adding an address to an unset allow-list sets it; only unsetting it admits everyone again.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdmissionLists {
+    #[serde(default)]
+    pub sender_allow: Option<BTreeSet<Address>>,
+    #[serde(default)]
+    pub sender_deny: BTreeSet<Address>,
+    #[serde(default)]
+    pub recipient_allow: Option<BTreeSet<Address>>,
+    #[serde(default)]
+    pub recipient_deny: BTreeSet<Address>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AdmissionList {
+    SenderAllow,
+    SenderDeny,
+    RecipientAllow,
+    RecipientDeny,
+}
+
+#[cfg_attr(not(feature = "client"), rpc(server, namespace = "admin"))]
+#[cfg_attr(feature = "client", rpc(server, client, namespace = "admin"))]
+pub trait AdmissionApi {
+    #[method(name = "admissionLists")]
+    async fn admission_lists(&self) -> RpcResult<AdmissionLists>;
+
+    /// Adds `address` to `list`; adding to an unset allow-list restricts admission to it.
+    #[method(name = "admissionAdd")]
+    async fn admission_add(&self, list: AdmissionList, address: Address) -> RpcResult<bool>;
+
+    /// Removes `address` from `list`; an allow-list left empty admits no one.
+    #[method(name = "admissionRemove")]
+    async fn admission_remove(&self, list: AdmissionList, address: Address) -> RpcResult<bool>;
+
+    /// Unsets allow-list `list`, admitting everyone, or empties deny-list `list`.
+    #[method(name = "admissionUnset")]
+    async fn admission_unset(&self, list: AdmissionList) -> RpcResult<bool>;
+
+    #[method(name = "admissionReload")]
+    async fn admission_reload(&self) -> RpcResult<AdmissionLists>;
+}
@@ -2,3 +2,8 @@ pub mod traits {
     pub use reth_rpc_api::EngineApiServer;
     pub use reth_rpc_api::EthApiServer;
 }
+
+pub mod admission;
+pub mod miner;
+pub mod quota;
+pub mod redstone;
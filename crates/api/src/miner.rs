@@ -0,0 +1,47 @@
+use alloy_primitives::{Bytes, U256, U64};
+use jsonrpsee::core::RpcResult;
+use jsonrpsee::proc_macros::rpc;
+
+/// The block production parameters adjustable at runtime.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MinerParams {
+    /// The lowest priority fee a transaction has to offer to be accepted and included.
+    pub gas_price: Option<U256>,
+    /// The gas the sequencer's transactions may take up in a block.
+    pub gas_limit: Option<U64>,
+    /// The extra-data of the blocks, as set on the backend.
+    pub extra: Bytes,
+    /// The largest estimated DA size of a transaction included in a block, in bytes.
+    #[serde(rename = "maxTxDASize")]
+    pub max_tx_da_size: Option<U64>,
+    /// The largest cumulative estimated DA size of the transactions in a block, in bytes.
+    #[serde(rename = "maxBlockDASize")]
+    pub max_block_da_size: Option<U64>,
+}
+
+#[cfg_attr(not(feature = "client"), rpc(server, namespace = "miner"))]
+#[cfg_attr(feature = "client", rpc(server, client, namespace = "miner"))]
+pub trait MinerApi {
+    /// Limits the estimated compressed DA size of a single transaction and of a whole block.
+    /// Zero means no limit.
+    #[method(name = "setMaxDASize")]
+    async fn set_max_da_size(&self, max_tx_size: U64, max_block_size: U64) -> RpcResult<bool>;
+
+    /// Sets the lowest priority fee accepted, here and by the backend's miner.
+    /// Zero means no limit.
+    #[method(name = "setGasPrice")]
+    async fn set_gas_price(&self, gas_price: U256) -> RpcResult<bool>;
+
+    /// Limits the gas the transactions may take up in a block, here and in the backend's miner.
+    /// Zero means no limit.
+    #[method(name = "setGasLimit")]
+    async fn set_gas_limit(&self, gas_limit: U64) -> RpcResult<bool>;
+
+    /// Sets the extra-data of the blocks built by the backend.
+    #[method(name = "setExtra")]
+    async fn set_extra(&self, extra: Bytes) -> RpcResult<bool>;
+
+    #[method(name = "getParams")]
+    async fn get_params(&self) -> RpcResult<MinerParams>;
+}
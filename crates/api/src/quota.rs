@@ -0,0 +1,30 @@
+use std::collections::BTreeMap;
+
+use jsonrpsee::core::RpcResult;
+use jsonrpsee::proc_macros::rpc;
+
+/// Compute-units spent by an API-key in the current windows.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiKeyUsage {
+    pub tier: String,
+    pub minute_used: u64,
+    pub minute_limit: Option<u64>,
+    pub day_used: u64,
+    pub day_limit: Option<u64>,
+    pub total_used: u64,
+}
+
+#[cfg_attr(not(feature = "client"), rpc(server, namespace = "admin"))]
+#[cfg_attr(feature = "client", rpc(server, client, namespace = "admin"))]
+pub trait QuotaApi {
+    /// The usage of `api_key`, or of every known API-key if `api_key` is not specified.
+    #[method(name = "apiKeyUsage")]
+    async fn api_key_usage(
+        &self,
+        api_key: Option<String>,
+    ) -> RpcResult<BTreeMap<String, ApiKeyUsage>>;
+
+    #[method(name = "apiKeysReload")]
+    async fn api_keys_reload(&self) -> RpcResult<usize>;
+}
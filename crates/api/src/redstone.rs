@@ -0,0 +1,47 @@
+use alloy_primitives::{Bytes, U256};
+use alloy_rpc_types::state::StateOverride;
+use alloy_rpc_types::{BlockId, TransactionRequest};
+use jsonrpsee::core::RpcResult;
+use jsonrpsee::proc_macros::rpc;
+
+/// The L1 data fee of a transaction and the parameters it has been computed with.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct L1FeeEstimate {
+    /// The L1 data fee, in wei.
+    pub l1_fee: U256,
+    /// The size of the transaction the fee is charged for: the FastLZ-based estimate since Fjord,
+    /// the calldata-gas divided by 16 before.
+    pub estimated_size: u64,
+    pub l1_base_fee: U256,
+    pub blob_base_fee: U256,
+    pub base_fee_scalar: u32,
+    pub blob_base_fee_scalar: u32,
+    pub fjord: bool,
+}
+
+/// The result of `eth_estimateGas` extended with the L1 data fee.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GasEstimate {
+    pub gas: U256,
+    /// An upper bound of the L1 data fee: the request is not signed yet.
+    pub l1_fee: L1FeeEstimate,
+}
+
+#[cfg_attr(not(feature = "client"), rpc(server, namespace = "redstone"))]
+#[cfg_attr(feature = "client", rpc(server, client, namespace = "redstone"))]
+pub trait RedstoneApi {
+    /// The L1 data fee the signed transaction `transaction` would be charged if included now.
+    #[method(name = "estimateL1Fee")]
+    async fn estimate_l1_fee(&self, transaction: Bytes) -> RpcResult<L1FeeEstimate>;
+
+    /// Same as `eth_estimateGas`, but also reports the L1 data fee of the transaction.
+    #[method(name = "estimateGas")]
+    async fn estimate_gas(
+        &self,
+        request: TransactionRequest,
+        block_number: Option<BlockId>,
+        state_override: Option<StateOverride>,
+    ) -> RpcResult<GasEstimate>;
+}
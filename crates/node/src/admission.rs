@@ -0,0 +1,225 @@
+pub mod rules;
+
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+pub use ::api::admission::{AdmissionList, AdmissionLists};
+use alloy_primitives::Address;
+
+use crate::AnyError;
+
+/// File-backed sender/recipient lists checked before a transaction is accepted.
+#[derive(Debug, Default)]
+pub struct Admission {
+    path: Option<PathBuf>,
+    lists: RwLock<AdmissionLists>,
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum Rejection {
+    #[error("sender {0} is denied")]
+    SenderDenied(Address),
+    #[error("sender {0} is not allowed")]
+    SenderNotAllowed(Address),
+    #[error("recipient {0} is denied")]
+    RecipientDenied(Address),
+    #[error("recipient {0} is not allowed")]
+    RecipientNotAllowed(Address),
+    #[error("denied by rule {0:?}")]
+    DeniedByRule(String),
+    #[error("priority fee {tip} is below the minimum of {min}")]
+    Underpriced { tip: u128, min: u128 },
+}
+
+impl Rejection {
+    pub fn reason(&self) -> &'static str {
+        match self {
+            Self::SenderDenied(_) => "sender_denied",
+            Self::SenderNotAllowed(_) => "sender_not_allowed",
+            Self::RecipientDenied(_) => "recipient_denied",
+            Self::RecipientNotAllowed(_) => "recipient_not_allowed",
+            Self::DeniedByRule(_) => "rule_denied",
+            Self::Underpriced { .. } => "underpriced",
+        }
+    }
+}
+
+impl Admission {
+    /// Loads the lists from `path`; a missing file is treated as empty lists.
+    pub fn load(path: Option<PathBuf>) -> Result<Self, AnyError> {
+        let lists = match path.as_deref() {
+            Some(path) => read_lists(path)?,
+            None => Default::default(),
+        };
+        Ok(Self {
+            path,
+            lists: RwLock::new(lists),
+        })
+    }
+
+    /// Takes the lists as given rather than from a file: the changes to them are not persisted.
+    pub fn new(lists: AdmissionLists) -> Self {
+        Self {
+            path: None,
+            lists: RwLock::new(lists),
+        }
+    }
+
+    /// Replaces the lists taken as given.
+    pub fn replace(&self, lists: AdmissionLists) {
+        let mut current = self.lists.write().expect("rw-lock.write -> poisoned");
+        log_transitions(&current, &lists);
+        *current = lists;
+    }
+
+    pub fn lists(&self) -> AdmissionLists {
+        self.lists.read().expect("rw-lock.read -> poisoned").clone()
+    }
+
+    pub fn reload(&self) -> Result<AdmissionLists, AnyError> {
+        let Some(path) = self.path.as_deref() else {
+            return Ok(self.lists());
+        };
+        let lists = read_lists(path)?;
+        let mut current = self.lists.write().expect("rw-lock.write -> poisoned");
+        log_transitions(&current, &lists);
+        *current = lists.clone();
+        drop(current);
+        tracing::info!("reloaded admission lists from {:?}", path);
+        Ok(lists)
+    }
+
+    pub fn check(&self, sender: Address, recipient: Option<Address>) -> Result<(), Rejection> {
+        let lists = self.lists.read().expect("rw-lock.read -> poisoned");
+
+        if lists.sender_deny.contains(&sender) {
+            return Err(Rejection::SenderDenied(sender));
+        }
+        if lists
+            .sender_allow
+            .as_ref()
+            .is_some_and(|allow| !allow.contains(&sender))
+        {
+            return Err(Rejection::SenderNotAllowed(sender));
+        }
+        if let Some(recipient) = recipient {
+            if lists.recipient_deny.contains(&recipient) {
+                return Err(Rejection::RecipientDenied(recipient));
+            }
+            if lists
+                .recipient_allow
+                .as_ref()
+                .is_some_and(|allow| !allow.contains(&recipient))
+            {
+                return Err(Rejection::RecipientNotAllowed(recipient));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Adds `address` to `list` and persists the lists. Returns `false` if it was already there.
+    ///
+    /// Adding to an allow-list that is not set sets it: from then on, only the addresses on it
+    /// are admitted.
+    pub fn insert(&self, list: AdmissionList, address: Address) -> Result<bool, AnyError> {
+        self.modify(|lists| {
+            match list {
+                AdmissionList::SenderAllow => {
+                    lists.sender_allow.get_or_insert_with(Default::default)
+                }
+                AdmissionList::SenderDeny => &mut lists.sender_deny,
+                AdmissionList::RecipientAllow => {
+                    lists.recipient_allow.get_or_insert_with(Default::default)
+                }
+                AdmissionList::RecipientDeny => &mut lists.recipient_deny,
+            }
+            .insert(address)
+        })
+    }
+
+    /// Removes `address` from `list` and persists the lists. Returns `false` if it was not there.
+    ///
+    /// An allow-list stays set once its last address is removed: no one is admitted.
+    pub fn remove(&self, list: AdmissionList, address: Address) -> Result<bool, AnyError> {
+        self.modify(|lists| match list {
+            AdmissionList::SenderAllow => lists
+                .sender_allow
+                .as_mut()
+                .is_some_and(|allow| allow.remove(&address)),
+            AdmissionList::SenderDeny => lists.sender_deny.remove(&address),
+            AdmissionList::RecipientAllow => lists
+                .recipient_allow
+                .as_mut()
+                .is_some_and(|allow| allow.remove(&address)),
+            AdmissionList::RecipientDeny => lists.recipient_deny.remove(&address),
+        })
+    }
+
+    /// Unsets allow-list `list`, so that everyone is admitted, or empties deny-list `list`,
+    /// and persists the lists. Returns `false` if it was already so.
+    pub fn unset(&self, list: AdmissionList) -> Result<bool, AnyError> {
+        self.modify(|lists| match list {
+            AdmissionList::SenderAllow => lists.sender_allow.take().is_some(),
+            AdmissionList::SenderDeny => !std::mem::take(&mut lists.sender_deny).is_empty(),
+            AdmissionList::RecipientAllow => lists.recipient_allow.take().is_some(),
+            AdmissionList::RecipientDeny => !std::mem::take(&mut lists.recipient_deny).is_empty(),
+        })
+    }
+
+    fn modify(&self, f: impl FnOnce(&mut AdmissionLists) -> bool) -> Result<bool, AnyError> {
+        let mut lists = self.lists.write().expect("rw-lock.write -> poisoned");
+        let mut updated = lists.clone();
+        if !f(&mut updated) {
+            return Ok(false);
+        }
+        if let Some(path) = self.path.as_deref() {
+            write_lists(path, &updated)?;
+        }
+        log_transitions(&lists, &updated);
+        *lists = updated;
+        Ok(true)
+    }
+}
+
+/// Logs the allow-lists that have been set, emptied or unset from `old` to `new`.
+fn log_transitions(old: &AdmissionLists, new: &AdmissionLists) {
+    for (list, old, new) in [
+        (
+            AdmissionList::SenderAllow,
+            &old.sender_allow,
+            &new.sender_allow,
+        ),
+        (
+            AdmissionList::RecipientAllow,
+            &old.recipient_allow,
+            &new.recipient_allow,
+        ),
+    ] {
+        match (old, new) {
+            (None, Some(_)) => {
+                tracing::warn!("{:?} set: only the addresses on it are admitted", list)
+            }
+            (Some(_), None) => tracing::warn!("{:?} unset: everyone is admitted", list),
+            (Some(old), Some(new)) if !old.is_empty() && new.is_empty() => {
+                tracing::warn!("{:?} emptied: no one is admitted", list)
+            }
+            _ => (),
+        }
+    }
+}
+
+fn read_lists(path: &Path) -> Result<AdmissionLists, AnyError> {
+    match std::fs::read(path) {
+        Ok(bytes) => Ok(serde_json::from_slice(&bytes)?),
+        Err(reason) if reason.kind() == std::io::ErrorKind::NotFound => Ok(Default::default()),
+        Err(reason) => Err(reason.into()),
+    }
+}
+
+fn write_lists(path: &Path, lists: &AdmissionLists) -> Result<(), AnyError> {
+    let tmp_path = path.with_extension("tmp");
+    std::fs::write(&tmp_path, serde_json::to_vec_pretty(lists)?)?;
+    std::fs::rename(&tmp_path, path)?;
+    Ok(())
+}
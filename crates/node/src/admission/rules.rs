@@ -0,0 +1,213 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use std::time::{Duration, SystemTime};
+
+use alloy_primitives::{Address, Selector, U256};
+use reth_primitives::TransactionSignedEcRecovered;
+
+use crate::AnyError;
+
+/// The contents of a rules-file.
+///
+/// Rules are evaluated in order; the first rule that matches a transaction decides its fate.
+/// A transaction that matches no rule is allowed.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleSet {
+    #[serde(default)]
+    pub rules: Vec<Rule>,
+
+    /// How long a deprioritized transaction is held back before being forwarded.
+    #[serde(default = "default_deprioritize_delay_ms")]
+    pub deprioritize_delay_ms: u64,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Rule {
+    pub name: String,
+    #[serde(rename = "match", default)]
+    pub matcher: Matcher,
+    pub action: Action,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Action {
+    Allow,
+    Deny,
+    Deprioritize,
+}
+
+/// The conditions a transaction has to meet for a [`Rule`] to apply.
+///
+/// Every specified condition must hold; an empty matcher matches any transaction.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Matcher {
+    pub sender: Option<BTreeSet<Address>>,
+    pub sender_not: Option<BTreeSet<Address>>,
+    pub recipient: Option<BTreeSet<Address>>,
+    pub recipient_not: Option<BTreeSet<Address>>,
+    pub selector: Option<BTreeSet<Selector>>,
+    pub create: Option<bool>,
+    pub min_value: Option<U256>,
+    pub max_value: Option<U256>,
+    pub min_gas_limit: Option<u64>,
+    pub max_gas_limit: Option<u64>,
+    pub tx_type: Option<BTreeSet<u8>>,
+    pub min_calldata_size: Option<usize>,
+    pub max_calldata_size: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Allow,
+    Deny(String),
+    Deprioritize(String),
+}
+
+/// A [`RuleSet`] loaded from a file, reloaded whenever the file's modification time changes.
+#[derive(Debug, Default)]
+pub struct Rules {
+    path: Option<PathBuf>,
+    loaded: RwLock<Loaded>,
+}
+
+#[derive(Debug, Default)]
+struct Loaded {
+    rule_set: RuleSet,
+    modified: Option<SystemTime>,
+}
+
+impl Rules {
+    pub fn load(path: Option<PathBuf>) -> Result<Self, AnyError> {
+        let loaded = match path.as_deref() {
+            Some(path) => read_rule_set(path)?,
+            None => Default::default(),
+        };
+        Ok(Self {
+            path,
+            loaded: RwLock::new(loaded),
+        })
+    }
+
+    /// Re-reads the rules-file if it has been modified since it was last read.
+    /// Returns `true` if the rules have been replaced.
+    pub fn reload_if_changed(&self) -> Result<bool, AnyError> {
+        let Some(path) = self.path.as_deref() else {
+            return Ok(false);
+        };
+        let modified = std::fs::metadata(path)?.modified()?;
+        if self
+            .loaded
+            .read()
+            .expect("rw-lock.read -> poisoned")
+            .modified
+            .is_some_and(|loaded| loaded == modified)
+        {
+            return Ok(false);
+        }
+
+        let loaded = read_rule_set(path)?;
+        tracing::info!(
+            "reloaded {} admission rules from {:?}",
+            loaded.rule_set.rules.len(),
+            path
+        );
+        *self.loaded.write().expect("rw-lock.write -> poisoned") = loaded;
+        Ok(true)
+    }
+
+    pub fn deprioritize_delay(&self) -> Duration {
+        Duration::from_millis(
+            self.loaded
+                .read()
+                .expect("rw-lock.read -> poisoned")
+                .rule_set
+                .deprioritize_delay_ms,
+        )
+    }
+
+    pub fn evaluate(&self, transaction: &TransactionSignedEcRecovered) -> Verdict {
+        self.loaded
+            .read()
+            .expect("rw-lock.read -> poisoned")
+            .rule_set
+            .evaluate(transaction)
+    }
+}
+
+impl RuleSet {
+    pub fn evaluate(&self, transaction: &TransactionSignedEcRecovered) -> Verdict {
+        let Some(rule) = self
+            .rules
+            .iter()
+            .find(|rule| rule.matcher.matches(transaction))
+        else {
+            return Verdict::Allow;
+        };
+        match rule.action {
+            Action::Allow => Verdict::Allow,
+            Action::Deny => Verdict::Deny(rule.name.clone()),
+            Action::Deprioritize => Verdict::Deprioritize(rule.name.clone()),
+        }
+    }
+}
+
+impl Matcher {
+    pub fn matches(&self, transaction: &TransactionSignedEcRecovered) -> bool {
+        let sender = transaction.signer();
+        let recipient = transaction.to();
+        let input = transaction.input();
+        let value = U256::from(transaction.value());
+        let gas_limit = transaction.gas_limit();
+
+        is_in(&self.sender, &sender)
+            && is_not_in(&self.sender_not, &sender)
+            && recipient.map_or(self.recipient.is_none(), |r| is_in(&self.recipient, &r))
+            && recipient.map_or(true, |r| is_not_in(&self.recipient_not, &r))
+            && self.selector.as_ref().map_or(true, |selectors| {
+                input
+                    .get(..4)
+                    .is_some_and(|s| selectors.contains(&Selector::from_slice(s)))
+            })
+            && self
+                .create
+                .map_or(true, |create| create == recipient.is_none())
+            && self.min_value.map_or(true, |min| value >= min)
+            && self.max_value.map_or(true, |max| value <= max)
+            && self.min_gas_limit.map_or(true, |min| gas_limit >= min)
+            && self.max_gas_limit.map_or(true, |max| gas_limit <= max)
+            && is_in(&self.tx_type, &u8::from(transaction.tx_type()))
+            && self
+                .min_calldata_size
+                .map_or(true, |min| input.len() >= min)
+            && self
+                .max_calldata_size
+                .map_or(true, |max| input.len() <= max)
+    }
+}
+
+fn is_in<T: Ord>(set: &Option<BTreeSet<T>>, item: &T) -> bool {
+    set.as_ref().map_or(true, |set| set.contains(item))
+}
+
+fn is_not_in<T: Ord>(set: &Option<BTreeSet<T>>, item: &T) -> bool {
+    set.as_ref().map_or(true, |set| !set.contains(item))
+}
+
+fn read_rule_set(path: &Path) -> Result<Loaded, AnyError> {
+    let modified = std::fs::metadata(path)?.modified()?;
+    let rule_set = serde_json::from_slice(&std::fs::read(path)?)
+        .map_err(|reason| format!("invalid rules-file {:?}: {}", path, reason))?;
+    Ok(Loaded {
+        rule_set,
+        modified: Some(modified),
+    })
+}
+
+fn default_deprioritize_delay_ms() -> u64 {
+    2_000
+}
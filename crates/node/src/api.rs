@@ -1,30 +1,90 @@
+mod admission_api;
+mod deprioritized;
 mod engine_api;
 mod eth_api;
 mod eth_filter_api;
+mod eth_pubsub_api;
+mod gas_oracle;
+mod heads;
+mod miner_api;
+mod pending_block;
+mod pin_latest;
+mod pool;
+mod preflight;
+mod redstone_api;
+mod response_cache;
+mod signer;
+mod txpool_api;
 
 use std::sync::Arc;
 use std::sync::RwLock;
 
+use jsonrpsee::core::RpcResult;
 use jsonrpsee::http_client::transport::HttpBackend;
 use jsonrpsee::http_client::HttpClient;
+use tokio::sync::broadcast;
 
-use reth_primitives::U256;
+pub use ::api::admission::AdmissionApiServer;
+pub use ::api::miner::MinerApiServer;
+pub use ::api::redstone::RedstoneApiServer;
+use alloy_primitives::{Bytes, B256};
+use reth_primitives::{TransactionSigned, TransactionSignedEcRecovered, U256};
 use reth_rpc::JwtSecret;
 pub use reth_rpc_api::EngineApiServer;
 pub use reth_rpc_api::EthApiServer;
 pub use reth_rpc_api::EthFilterApiServer;
+pub use reth_rpc_api::EthPubSubApiServer;
+pub use reth_rpc_api::TxPoolApiServer;
 
+use crate::admission::rules::Rules;
+use crate::admission::{Admission, Rejection};
 use crate::auth_layer::AddJwtHeader;
+use crate::coalesce::Coalescer;
+use crate::error::ProxyError;
+use crate::filters::Filters;
+use crate::gas_oracle::GasOracle;
+use crate::head_tracker::HeadTracker;
+use crate::l1_fee::L1FeeParams;
+use crate::logs::{LogsChunking, LogsLimits};
+use crate::miner::Miner;
+use crate::pool::journal::Journal;
+use crate::pool::Pool;
+use crate::response_cache::ResponseCache;
+use crate::routing::Router;
+use crate::signer::Signer;
 use crate::AnyError;
 
+use self::deprioritized::DeprioritizedQueue;
+use self::eth_pubsub_api::{LogsFeed, PENDING_TRANSACTIONS_CAPACITY};
+use self::pending_block::{PendingBlock, PendingPayload};
+
+/// The state shared by both servers, and how the server at hand serves it.
 #[derive(Debug, Clone)]
-pub struct Api(Arc<Inner>);
+pub struct Api(Arc<Inner>, Arc<ServerConfig>);
+
+/// What differs in how the servers serve the same state.
+#[derive(Debug, Clone, Default)]
+pub struct ServerConfig {
+    pub logs_limits: LogsLimits,
+    /// Signs for `eth_sendTransaction` and `eth_sign*`; without one these go to the backend.
+    pub signer: Option<Arc<Signer>>,
+    /// Forward `latest` as the head `eth_blockNumber` reports, so that reads agree with it.
+    pub pin_latest: bool,
+}
 
 impl Api {
+    #[allow(clippy::too_many_arguments)]
     pub async fn new(
-        eth_api_url: &str,
+        router: Router,
         engine_api_url: &str,
         engine_api_secret: JwtSecret,
+        admission: Admission,
+        rules: Rules,
+        journal: Option<Journal>,
+        gas_oracle: GasOracle,
+        response_cache: ResponseCache,
+        filters: Filters,
+        logs_chunking: LogsChunking,
     ) -> Result<Self, AnyError> {
         let engine_api_client = jsonrpsee::http_client::HttpClient::<HttpBackend>::builder()
             .set_http_middleware(
@@ -33,35 +93,109 @@ impl Api {
             )
             .build(engine_api_url)?;
 
-        let eth_api_client =
-            jsonrpsee::http_client::HttpClient::<HttpBackend>::builder().build(eth_api_url)?;
-
-        Ok(Self(Arc::new(Inner {
-            anonymous_client: eth_api_client,
+        let inner = Inner {
+            router,
             authenticated_client: engine_api_client,
             current_block_number: Default::default(),
-        })))
+            admission,
+            rules,
+            pool: Default::default(),
+            deprioritized: Default::default(),
+            journal,
+            gas_oracle,
+            pool_tip_cutoff: Default::default(),
+            l1_fee_params: Default::default(),
+            miner: Default::default(),
+            response_cache,
+            coalescer: Default::default(),
+            heads: Default::default(),
+            pending_transactions: broadcast::channel(PENDING_TRANSACTIONS_CAPACITY).0,
+            logs_feed: Default::default(),
+            filters,
+            logs_chunking,
+            pending_payload: Default::default(),
+            pending_block: Default::default(),
+        };
+        Ok(Self(Arc::new(inner), Default::default()))
+    }
+
+    /// The same state, served as per `config`.
+    pub fn for_server(&self, config: ServerConfig) -> Self {
+        Self(self.0.clone(), Arc::new(config))
+    }
+
+    pub fn admission(&self) -> &Admission {
+        &self.0.admission
+    }
+
+    pub fn rules(&self) -> &Rules {
+        &self.0.rules
+    }
+
+    /// Polls the head of every backend node; returns the head of the `default` backend.
+    pub async fn poll_backends(&self) -> Option<u64> {
+        self.0.router.poll().await
     }
 
     pub fn set_current_block_number(&self, block_number: U256) {
+        self.0.router.set_head(block_number.saturating_to());
         *self.0.current_block_number.write().expect("rw-lock.write -> poisoned") = block_number;
     }
 }
 
 #[derive(Debug)]
 struct Inner {
-    anonymous_client: HttpClient<HttpBackend>,
+    router: Router,
     authenticated_client: HttpClient<AddJwtHeader<HttpBackend>>,
     current_block_number: RwLock<U256>,
+    admission: Admission,
+    rules: Rules,
+    pool: Pool,
+    deprioritized: DeprioritizedQueue,
+    journal: Option<Journal>,
+    gas_oracle: GasOracle,
+    /// The tip outbidding the pool and the block it has been computed at.
+    pool_tip_cutoff: RwLock<Option<(u64, Option<u128>)>>,
+    /// The L1 data fee parameters and the block they have been read at.
+    l1_fee_params: RwLock<Option<(U256, L1FeeParams)>>,
+    miner: Miner,
+    response_cache: ResponseCache,
+    coalescer: Coalescer,
+    heads: HeadTracker,
+    /// The transactions accepted by the sequencer, as they come in.
+    pending_transactions: broadcast::Sender<TransactionSignedEcRecovered>,
+    logs_feed: LogsFeed,
+    filters: Filters,
+    logs_chunking: LogsChunking,
+    pending_payload: RwLock<Option<PendingPayload>>,
+    /// The pending block and the head and pool generation it has been built for.
+    pending_block: RwLock<Option<((B256, u64), PendingBlock)>>,
 }
 
-fn to_error_object(error: jsonrpsee::core::ClientError) -> jsonrpsee::types::ErrorObjectOwned {
-    use jsonrpsee::core::ClientError;
+fn recover_raw_transaction(bytes: &Bytes) -> RpcResult<TransactionSignedEcRecovered> {
+    use jsonrpsee::types::error::INVALID_PARAMS_CODE;
     use jsonrpsee::types::ErrorObject;
 
-    if let ClientError::Call(error_object) = error {
-        error_object
-    } else {
-        ErrorObject::owned(-32000, error.to_string(), Some(""))
-    }
+    let transaction = TransactionSigned::decode_enveloped(&mut bytes.as_ref()).map_err(|e| {
+        ErrorObject::owned(
+            INVALID_PARAMS_CODE,
+            format!("failed to decode transaction: {}", e),
+            None::<()>,
+        )
+    })?;
+    transaction.into_ecrecovered().ok_or_else(|| {
+        ErrorObject::owned(
+            INVALID_PARAMS_CODE,
+            "invalid transaction signature",
+            None::<()>,
+        )
+    })
+}
+
+fn rejection_to_error_object(rejection: Rejection) -> jsonrpsee::types::ErrorObjectOwned {
+    ProxyError::PolicyRejection(rejection).into()
+}
+
+fn as_u128<T: TryInto<u128>>(value: T) -> u128 {
+    value.try_into().unwrap_or(u128::MAX)
 }
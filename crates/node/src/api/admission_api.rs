@@ -0,0 +1,45 @@
+use alloy_primitives::Address;
+use jsonrpsee::core::RpcResult;
+use jsonrpsee::types::ErrorObject;
+
+use crate::admission::{AdmissionList, AdmissionLists};
+use crate::AnyError;
+
+use super::{AdmissionApiServer, Api};
+
+#[async_trait::async_trait]
+impl AdmissionApiServer for Api {
+    async fn admission_lists(&self) -> RpcResult<AdmissionLists> {
+        Ok(self.0.admission.lists())
+    }
+
+    async fn admission_add(&self, list: AdmissionList, address: Address) -> RpcResult<bool> {
+        self.0
+            .admission
+            .insert(list, address)
+            .map_err(internal_error)
+    }
+
+    async fn admission_remove(&self, list: AdmissionList, address: Address) -> RpcResult<bool> {
+        self.0
+            .admission
+            .remove(list, address)
+            .map_err(internal_error)
+    }
+
+    async fn admission_unset(&self, list: AdmissionList) -> RpcResult<bool> {
+        self.0.admission.unset(list).map_err(internal_error)
+    }
+
+    async fn admission_reload(&self) -> RpcResult<AdmissionLists> {
+        self.0.admission.reload().map_err(internal_error)
+    }
+}
+
+fn internal_error(reason: AnyError) -> jsonrpsee::types::ErrorObjectOwned {
+    ErrorObject::owned(
+        jsonrpsee::types::error::INTERNAL_ERROR_CODE,
+        reason.to_string(),
+        None::<()>,
+    )
+}
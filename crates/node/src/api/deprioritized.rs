@@ -0,0 +1,116 @@
+use std::sync::Mutex;
+
+use alloy_primitives::{Bytes, B256};
+use jsonrpsee::core::RpcResult;
+use reth_primitives::TransactionSignedEcRecovered;
+use reth_rpc_api::EthApiClient;
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+
+use crate::error::{is_unanswered, ProxyError, RateLimit};
+
+use super::Api;
+
+/// The most deprioritized transactions waiting to be forwarded at once.
+pub(super) const DEPRIORITIZED_CAPACITY: usize = 1024;
+
+/// The deprioritized transactions, in the order they are forwarded to the backend.
+#[derive(Debug)]
+pub(super) struct DeprioritizedQueue {
+    sender: mpsc::Sender<Deprioritized>,
+    /// Taken by the task forwarding them.
+    receiver: Mutex<Option<mpsc::Receiver<Deprioritized>>>,
+}
+
+#[derive(Debug)]
+struct Deprioritized {
+    due_at: Instant,
+    hash: B256,
+    raw: Bytes,
+}
+
+impl Default for DeprioritizedQueue {
+    fn default() -> Self {
+        let (sender, receiver) = mpsc::channel(DEPRIORITIZED_CAPACITY);
+        Self {
+            sender,
+            receiver: Mutex::new(Some(receiver)),
+        }
+    }
+}
+
+impl Api {
+    /// Pools `transaction` and queues it to be forwarded once the deprioritizing delay is over;
+    /// rejected while the queue is full.
+    pub(super) fn deprioritize(
+        &self,
+        transaction: TransactionSignedEcRecovered,
+        raw: Bytes,
+    ) -> RpcResult<()> {
+        let delay = self.0.rules.deprioritize_delay();
+        let queue = &self.0.deprioritized.sender;
+        let permit = queue.try_reserve().map_err(|_| ProxyError::RateLimited {
+            limit: RateLimit::Class("deprioritized"),
+            retry_after: delay,
+        })?;
+        permit.send(Deprioritized {
+            due_at: Instant::now() + delay,
+            hash: transaction.hash(),
+            raw: raw.clone(),
+        });
+        self.accept(transaction, raw);
+        Ok(())
+    }
+
+    /// Forwards the deprioritized transactions to the primary as they come due;
+    /// those it rejects are dropped from the pool and the journal, but not those it fails to
+    /// answer for.
+    pub async fn forward_deprioritized(&self) {
+        let receiver = self
+            .0
+            .deprioritized
+            .receiver
+            .lock()
+            .expect("mutex.lock -> poisoned")
+            .take();
+        let Some(mut receiver) = receiver else {
+            return std::future::pending().await;
+        };
+
+        while let Some(deprioritized) = receiver.recv().await {
+            tokio::time::sleep_until(deprioritized.due_at).await;
+            let forwarded = self
+                .0
+                .router
+                .default_backend()
+                .call_primary("eth_sendRawTransaction", |client| {
+                    client.send_raw_transaction(deprioritized.raw.clone())
+                })
+                .await;
+            let Err(reason) = forwarded else {
+                continue;
+            };
+            // Kept in the pool and the journal, until included or expired, as it may have been
+            // received.
+            if is_unanswered(&reason) {
+                tracing::warn!(
+                    "failed to forward deprioritized {}: {}",
+                    deprioritized.hash,
+                    reason.message()
+                );
+                continue;
+            }
+
+            tracing::info!(
+                "dropping deprioritized {}: {}",
+                deprioritized.hash,
+                reason.message()
+            );
+            if self.0.pool.remove(&deprioritized.hash).is_some() {
+                if let Err(reason) = self.compact_pool_journal() {
+                    tracing::warn!("failed to compact the pool-journal: {}", reason);
+                }
+            }
+        }
+    }
+}
@@ -6,15 +6,20 @@ use alloy_rpc_types_engine::{
 use alloy_rpc_types_engine::{
     ExecutionPayloadInputV2, ExecutionPayloadV1, ExecutionPayloadV3, PayloadStatus,
 };
-use jsonrpsee::core::RpcResult;
+use jsonrpsee::core::{ClientError, RpcResult};
+use jsonrpsee::types::ErrorObjectOwned;
 use reth_node_api::EngineTypes;
 use reth_rpc_api::{EngineApiClient, EngineApiServer};
 
 use reth_node_optimism::OptimismEngineTypes;
 
-use super::to_error_object;
+use crate::error::ProxyError;
+
 use super::Api;
 
+/// The name the Engine API node goes by in the errors.
+const ENGINE_BACKEND: &str = "engine";
+
 impl Api {
     pub fn backend_engine_api(&self) -> &impl EngineApiClient<OptimismEngineTypes> {
         &self.0.authenticated_client
@@ -27,14 +32,14 @@ impl EngineApiServer<OptimismEngineTypes> for Api {
         self.backend_engine_api()
             .new_payload_v1(payload)
             .await
-            .map_err(to_error_object)
+            .map_err(engine_error)
     }
 
     async fn new_payload_v2(&self, payload: ExecutionPayloadInputV2) -> RpcResult<PayloadStatus> {
         self.backend_engine_api()
             .new_payload_v2(payload)
             .await
-            .map_err(to_error_object)
+            .map_err(engine_error)
     }
 
     async fn new_payload_v3(
@@ -46,7 +51,7 @@ impl EngineApiServer<OptimismEngineTypes> for Api {
         self.backend_engine_api()
             .new_payload_v3(payload, versioned_hashes, parent_beacon_block_root)
             .await
-            .map_err(to_error_object)
+            .map_err(engine_error)
     }
 
     async fn fork_choice_updated_v1(
@@ -54,10 +59,16 @@ impl EngineApiServer<OptimismEngineTypes> for Api {
         fork_choice_state: ForkchoiceState,
         payload_attributes: Option<<OptimismEngineTypes as EngineTypes>::PayloadAttributes>,
     ) -> RpcResult<ForkchoiceUpdated> {
-        self.backend_engine_api()
-            .fork_choice_updated_v1(fork_choice_state, payload_attributes)
+        let payload_attributes = self
+            .shape_payload_attributes(&fork_choice_state, payload_attributes)
+            .await;
+        let updated = self
+            .backend_engine_api()
+            .fork_choice_updated_v1(fork_choice_state, payload_attributes.clone())
             .await
-            .map_err(to_error_object)
+            .map_err(engine_error)?;
+        self.record_pending_payload(&fork_choice_state, &updated, payload_attributes);
+        Ok(updated)
     }
 
     async fn fork_choice_updated_v2(
@@ -65,10 +76,16 @@ impl EngineApiServer<OptimismEngineTypes> for Api {
         fork_choice_state: ForkchoiceState,
         payload_attributes: Option<<OptimismEngineTypes as EngineTypes>::PayloadAttributes>,
     ) -> RpcResult<ForkchoiceUpdated> {
-        self.backend_engine_api()
-            .fork_choice_updated_v2(fork_choice_state, payload_attributes)
+        let payload_attributes = self
+            .shape_payload_attributes(&fork_choice_state, payload_attributes)
+            .await;
+        let updated = self
+            .backend_engine_api()
+            .fork_choice_updated_v2(fork_choice_state, payload_attributes.clone())
             .await
-            .map_err(to_error_object)
+            .map_err(engine_error)?;
+        self.record_pending_payload(&fork_choice_state, &updated, payload_attributes);
+        Ok(updated)
     }
 
     async fn fork_choice_updated_v3(
@@ -76,10 +93,16 @@ impl EngineApiServer<OptimismEngineTypes> for Api {
         fork_choice_state: ForkchoiceState,
         payload_attributes: Option<<OptimismEngineTypes as EngineTypes>::PayloadAttributes>,
     ) -> RpcResult<ForkchoiceUpdated> {
-        self.backend_engine_api()
-            .fork_choice_updated_v3(fork_choice_state, payload_attributes)
+        let payload_attributes = self
+            .shape_payload_attributes(&fork_choice_state, payload_attributes)
+            .await;
+        let updated = self
+            .backend_engine_api()
+            .fork_choice_updated_v3(fork_choice_state, payload_attributes.clone())
             .await
-            .map_err(to_error_object)
+            .map_err(engine_error)?;
+        self.record_pending_payload(&fork_choice_state, &updated, payload_attributes);
+        Ok(updated)
     }
 
     async fn get_payload_v1(
@@ -89,7 +112,7 @@ impl EngineApiServer<OptimismEngineTypes> for Api {
         self.backend_engine_api()
             .get_payload_v1(payload_id)
             .await
-            .map_err(to_error_object)
+            .map_err(engine_error)
     }
 
     async fn get_payload_v2(
@@ -99,7 +122,7 @@ impl EngineApiServer<OptimismEngineTypes> for Api {
         self.backend_engine_api()
             .get_payload_v2(payload_id)
             .await
-            .map_err(to_error_object)
+            .map_err(engine_error)
     }
 
     async fn get_payload_v3(
@@ -109,7 +132,7 @@ impl EngineApiServer<OptimismEngineTypes> for Api {
         self.backend_engine_api()
             .get_payload_v3(payload_id)
             .await
-            .map_err(to_error_object)
+            .map_err(engine_error)
     }
 
     async fn get_payload_bodies_by_hash_v1(
@@ -119,7 +142,7 @@ impl EngineApiServer<OptimismEngineTypes> for Api {
         self.backend_engine_api()
             .get_payload_bodies_by_hash_v1(block_hashes)
             .await
-            .map_err(to_error_object)
+            .map_err(engine_error)
     }
 
     async fn get_payload_bodies_by_range_v1(
@@ -130,7 +153,7 @@ impl EngineApiServer<OptimismEngineTypes> for Api {
         self.backend_engine_api()
             .get_payload_bodies_by_range_v1(start, count)
             .await
-            .map_err(to_error_object)
+            .map_err(engine_error)
     }
 
     async fn exchange_transition_configuration(
@@ -140,13 +163,17 @@ impl EngineApiServer<OptimismEngineTypes> for Api {
         self.backend_engine_api()
             .exchange_transition_configuration(transition_configuration)
             .await
-            .map_err(to_error_object)
+            .map_err(engine_error)
     }
 
     async fn exchange_capabilities(&self, capabilities: Vec<String>) -> RpcResult<Vec<String>> {
         self.backend_engine_api()
             .exchange_capabilities(capabilities)
             .await
-            .map_err(to_error_object)
+            .map_err(engine_error)
     }
 }
+
+fn engine_error(error: ClientError) -> ErrorObjectOwned {
+    ProxyError::from_client_error(ENGINE_BACKEND, error)
+}
@@ -24,180 +24,336 @@ use alloy_rpc_types::Work;
 use jsonrpsee::core::RpcResult;
 use reth_primitives::serde_helper::JsonStorageKey;
 use reth_primitives::serde_helper::U64HexOrNumber;
+use reth_primitives::TransactionSignedEcRecovered;
 use reth_rpc_api::EthApiClient;
 use reth_rpc_api::EthApiServer;
 use reth_rpc_types::AnyTransactionReceipt;
 
-use super::to_error_object;
+use crate::admission::rules::Verdict;
+use crate::admission::Rejection;
+use crate::response_cache::Lifetime;
+use crate::routing::Route;
+use crate::signer::Signer;
+
+use super::signer::signer_error;
 use super::Api;
+use super::{recover_raw_transaction, rejection_to_error_object};
+
+/// A block holds while it is canonical.
+const BLOCK_LIFETIME: Lifetime = Lifetime::WhileCanonical {
+    number: "number",
+    hash: "hash",
+};
+
+/// A mined transaction, its receipt or the receipts of a block hold while the block is canonical;
+/// pending transactions are not cached, lacking a block.
+const MINED_LIFETIME: Lifetime = Lifetime::WhileCanonical {
+    number: "blockNumber",
+    hash: "blockHash",
+};
 
 impl Api {
     pub fn backend_eth_api(&self) -> &impl EthApiClient {
-        &self.0.anonymous_client
+        self.0.router.default_backend().primary()
+    }
+
+    /// The backend serving `method` for `block`, as per the routing table.
+    ///
+    /// A block hash is routed by the number the head tracker knows it by, if any. A read
+    /// pinned to the head goes to the nodes that have seen it, or else to the primary.
+    pub(super) fn route<'a>(&'a self, method: &'a str, block: Option<BlockId>) -> Route<'a> {
+        let pinned = self.pinned_number(block);
+        let head = self
+            .0
+            .current_block_number
+            .read()
+            .expect("rw-lock.read -> poisoned")
+            .saturating_to();
+        let block = block.map(|block| match block {
+            BlockId::Hash(hash) => self
+                .0
+                .heads
+                .number_of(&hash.block_hash)
+                .map_or(block, BlockId::from),
+            BlockId::Number(_) => block,
+        });
+        let route = self.0.router.route(method, block, head);
+        match pinned {
+            Some(number) => route.at_least(number),
+            None => route,
+        }
+    }
+
+    /// The fee history built from the gas-oracle's window, if it covers the requested range.
+    fn local_fee_history(
+        &self,
+        block_count: u64,
+        newest_block: BlockNumberOrTag,
+        reward_percentiles: Option<&[f64]>,
+    ) -> Option<FeeHistory> {
+        let newest = match newest_block {
+            BlockNumberOrTag::Number(number) => number,
+            BlockNumberOrTag::Latest | BlockNumberOrTag::Pending => {
+                self.gas_oracle().latest()?.number
+            }
+            _ => return None,
+        };
+        if block_count == 0 {
+            return None;
+        }
+        let oldest = newest.checked_sub(block_count - 1)?;
+        let blocks = self.gas_oracle().range(oldest, newest)?;
+        let next_base_fee = blocks.last()?.next_base_fee();
+
+        Some(FeeHistory {
+            base_fee_per_gas: blocks
+                .iter()
+                .map(|block| block.base_fee)
+                .chain(std::iter::once(next_base_fee))
+                .map(Into::into)
+                .collect(),
+            gas_used_ratio: blocks.iter().map(|block| block.gas_used_ratio()).collect(),
+            oldest_block: oldest.into(),
+            reward: reward_percentiles.map(|percentiles| {
+                blocks
+                    .iter()
+                    .map(|block| {
+                        percentiles
+                            .iter()
+                            .map(|p| block.reward_at(*p).into())
+                            .collect()
+                    })
+                    .collect()
+            }),
+            ..Default::default()
+        })
+    }
+
+    fn reject(
+        &self,
+        transaction: &TransactionSignedEcRecovered,
+        rejection: Rejection,
+    ) -> jsonrpsee::types::ErrorObjectOwned {
+        tracing::debug!("rejecting {}: {}", transaction.hash(), rejection);
+        metrics::counter!("sequencer_admission_rejected_total", "reason" => rejection.reason())
+            .increment(1);
+        rejection_to_error_object(rejection)
     }
 }
 
 #[async_trait::async_trait]
 impl EthApiServer for Api {
     async fn protocol_version(&self) -> RpcResult<U64> {
-        self.backend_eth_api()
-            .protocol_version()
+        self.route("eth_protocolVersion", None)
+            .call(|client| client.protocol_version())
             .await
-            .map_err(to_error_object)
     }
     fn syncing(&self) -> RpcResult<SyncStatus> {
         Ok(SyncStatus::None)
     }
     async fn author(&self) -> RpcResult<Address> {
-        self.backend_eth_api().author().await.map_err(to_error_object)
+        self.route("eth_coinbase", None)
+            .call(|client| client.author())
+            .await
     }
     fn accounts(&self) -> RpcResult<Vec<Address>> {
-        Ok(Default::default())
+        Ok(self.signer().map(Signer::accounts).unwrap_or_default())
     }
     fn block_number(&self) -> RpcResult<U256> {
         Ok(*self.0.current_block_number.read().expect("rw-lock.read -> poisoned"))
     }
     async fn chain_id(&self) -> RpcResult<Option<U64>> {
-        self.backend_eth_api().chain_id().await.map_err(to_error_object)
+        self.route("eth_chainId", None)
+            .call(|client| client.chain_id())
+            .await
     }
     async fn block_by_hash(&self, hash: B256, full: bool) -> RpcResult<Option<RichBlock>> {
-        self.backend_eth_api()
-            .block_by_hash(hash, full)
-            .await
-            .map_err(to_error_object)
+        self.cached(
+            "eth_getBlockByHash",
+            (hash, full),
+            Some(BLOCK_LIFETIME),
+            async {
+                self.route("eth_getBlockByHash", None)
+                    .call(|client| client.block_by_hash(hash, full))
+                    .await
+            },
+        )
+        .await
     }
     async fn block_by_number(
         &self,
         number: BlockNumberOrTag,
         full: bool,
     ) -> RpcResult<Option<RichBlock>> {
-        self.backend_eth_api()
-            .block_by_number(number, full)
-            .await
-            .map_err(to_error_object)
+        if let Some(block) = self
+            .local_pending_block(number)
+            .and_then(|pending| pending.to_rich_block(full))
+        {
+            return Ok(Some(block));
+        }
+        let pinned = self.pin_block_number(number);
+        self.coalesced("eth_getBlockByNumber", (pinned, full), async {
+            self.route("eth_getBlockByNumber", Some(number.into()))
+                .call(|client| client.block_by_number(pinned, full))
+                .await
+        })
+        .await
     }
     async fn block_transaction_count_by_hash(&self, hash: B256) -> RpcResult<Option<U256>> {
-        self.backend_eth_api()
-            .block_transaction_count_by_hash(hash)
+        self.route("eth_getBlockTransactionCountByHash", None)
+            .call(|client| client.block_transaction_count_by_hash(hash))
             .await
-            .map_err(to_error_object)
     }
     async fn block_transaction_count_by_number(
         &self,
         number: BlockNumberOrTag,
     ) -> RpcResult<Option<U256>> {
-        self.backend_eth_api()
-            .block_transaction_count_by_number(number)
+        if let Some(pending) = self.local_pending_block(number) {
+            return Ok(Some(U256::from(pending.transactions.len())));
+        }
+        let pinned = self.pin_block_number(number);
+        self.route("eth_getBlockTransactionCountByNumber", Some(number.into()))
+            .call(|client| client.block_transaction_count_by_number(pinned))
             .await
-            .map_err(to_error_object)
     }
     async fn block_uncles_count_by_hash(&self, hash: B256) -> RpcResult<Option<U256>> {
-        self.backend_eth_api()
-            .block_uncles_count_by_hash(hash)
+        self.route("eth_getUncleCountByBlockHash", None)
+            .call(|client| client.block_uncles_count_by_hash(hash))
             .await
-            .map_err(to_error_object)
     }
     async fn block_uncles_count_by_number(
         &self,
         number: BlockNumberOrTag,
     ) -> RpcResult<Option<U256>> {
-        self.backend_eth_api()
-            .block_uncles_count_by_number(number)
+        let pinned = self.pin_block_number(number);
+        self.route("eth_getUncleCountByBlockNumber", Some(number.into()))
+            .call(|client| client.block_uncles_count_by_number(pinned))
             .await
-            .map_err(to_error_object)
     }
     async fn block_receipts(
         &self,
         block_id: BlockId,
     ) -> RpcResult<Option<Vec<AnyTransactionReceipt>>> {
-        self.backend_eth_api()
-            .block_receipts(block_id)
-            .await
-            .map_err(to_error_object)
+        let pinned = self.pin_block_id(Some(block_id)).unwrap_or(block_id);
+        let lifetime = match pinned {
+            BlockId::Hash(_) | BlockId::Number(BlockNumberOrTag::Number(_)) => Some(MINED_LIFETIME),
+            _ => None,
+        };
+        self.cached("eth_getBlockReceipts", pinned, lifetime, async {
+            self.route("eth_getBlockReceipts", Some(block_id))
+                .call(|client| client.block_receipts(pinned))
+                .await
+        })
+        .await
     }
     async fn uncle_by_block_hash_and_index(
         &self,
         hash: B256,
         index: Index,
     ) -> RpcResult<Option<RichBlock>> {
-        self.backend_eth_api()
-            .uncle_by_block_hash_and_index(hash, index)
+        self.route("eth_getUncleByBlockHashAndIndex", None)
+            .call(|client| client.uncle_by_block_hash_and_index(hash, index))
             .await
-            .map_err(to_error_object)
     }
     async fn uncle_by_block_number_and_index(
         &self,
         number: BlockNumberOrTag,
         index: Index,
     ) -> RpcResult<Option<RichBlock>> {
-        self.backend_eth_api()
-            .uncle_by_block_number_and_index(number, index)
+        let pinned = self.pin_block_number(number);
+        self.route("eth_getUncleByBlockNumberAndIndex", Some(number.into()))
+            .call(|client| client.uncle_by_block_number_and_index(pinned, index))
             .await
-            .map_err(to_error_object)
     }
     async fn raw_transaction_by_hash(&self, hash: B256) -> RpcResult<Option<Bytes>> {
-        self.backend_eth_api()
-            .raw_transaction_by_hash(hash)
+        self.route("eth_getRawTransactionByHash", None)
+            .call(|client| client.raw_transaction_by_hash(hash))
             .await
-            .map_err(to_error_object)
     }
     async fn transaction_by_hash(&self, hash: B256) -> RpcResult<Option<Transaction>> {
-        self.backend_eth_api()
-            .transaction_by_hash(hash)
-            .await
-            .map_err(to_error_object)
+        self.cached(
+            "eth_getTransactionByHash",
+            hash,
+            Some(MINED_LIFETIME),
+            async {
+                self.route("eth_getTransactionByHash", None)
+                    .call(|client| client.transaction_by_hash(hash))
+                    .await
+            },
+        )
+        .await
     }
     async fn raw_transaction_by_block_hash_and_index(
         &self,
         hash: B256,
         index: Index,
     ) -> RpcResult<Option<Bytes>> {
-        self.backend_eth_api()
-            .raw_transaction_by_block_hash_and_index(hash, index)
+        self.route("eth_getRawTransactionByBlockHashAndIndex", None)
+            .call(|client| client.raw_transaction_by_block_hash_and_index(hash, index))
             .await
-            .map_err(to_error_object)
     }
     async fn transaction_by_block_hash_and_index(
         &self,
         hash: B256,
         index: Index,
     ) -> RpcResult<Option<Transaction>> {
-        self.backend_eth_api()
-            .transaction_by_block_hash_and_index(hash, index)
+        self.route("eth_getTransactionByBlockHashAndIndex", None)
+            .call(|client| client.transaction_by_block_hash_and_index(hash, index))
             .await
-            .map_err(to_error_object)
     }
     async fn raw_transaction_by_block_number_and_index(
         &self,
         number: BlockNumberOrTag,
         index: Index,
     ) -> RpcResult<Option<Bytes>> {
-        self.backend_eth_api()
-            .raw_transaction_by_block_number_and_index(number, index)
-            .await
-            .map_err(to_error_object)
+        if let Some(pending) = self.local_pending_block(number) {
+            return Ok(pending
+                .transactions
+                .get(usize::from(index))
+                .map(|tx| tx.envelope_encoded()));
+        }
+        let pinned = self.pin_block_number(number);
+        self.route(
+            "eth_getRawTransactionByBlockNumberAndIndex",
+            Some(number.into()),
+        )
+        .call(|client| client.raw_transaction_by_block_number_and_index(pinned, index))
+        .await
     }
     async fn transaction_by_block_number_and_index(
         &self,
         number: BlockNumberOrTag,
         index: Index,
     ) -> RpcResult<Option<Transaction>> {
-        self.backend_eth_api()
-            .transaction_by_block_number_and_index(number, index)
-            .await
-            .map_err(to_error_object)
+        if let Some(pending) = self.local_pending_block(number) {
+            return Ok(pending.transaction(usize::from(index)));
+        }
+        let pinned = self.pin_block_number(number);
+        self.route(
+            "eth_getTransactionByBlockNumberAndIndex",
+            Some(number.into()),
+        )
+        .call(|client| client.transaction_by_block_number_and_index(pinned, index))
+        .await
     }
     async fn transaction_receipt(&self, hash: B256) -> RpcResult<Option<AnyTransactionReceipt>> {
-        self.backend_eth_api()
-            .transaction_receipt(hash)
-            .await
-            .map_err(to_error_object)
+        self.cached(
+            "eth_getTransactionReceipt",
+            hash,
+            Some(MINED_LIFETIME),
+            async {
+                self.route("eth_getTransactionReceipt", None)
+                    .call(|client| client.transaction_receipt(hash))
+                    .await
+            },
+        )
+        .await
     }
     async fn balance(&self, address: Address, block_number: Option<BlockId>) -> RpcResult<U256> {
-        self.backend_eth_api()
-            .balance(address, block_number)
+        let pinned = self.pin_block_id(block_number);
+        self.route("eth_getBalance", block_number)
+            .call(|client| client.balance(address, pinned))
             .await
-            .map_err(to_error_object)
     }
     async fn storage_at(
         &self,
@@ -205,38 +361,44 @@ impl EthApiServer for Api {
         index: JsonStorageKey,
         block_number: Option<BlockId>,
     ) -> RpcResult<B256> {
-        self.backend_eth_api()
-            .storage_at(address, index, block_number)
+        let pinned = self.pin_block_id(block_number);
+        self.route("eth_getStorageAt", block_number)
+            .call(|client| client.storage_at(address, index, pinned))
             .await
-            .map_err(to_error_object)
     }
     async fn transaction_count(
         &self,
         address: Address,
         block_number: Option<BlockId>,
     ) -> RpcResult<U256> {
-        self.backend_eth_api()
-            .transaction_count(address, block_number)
+        let pinned = self.pin_block_id(block_number);
+        self.route("eth_getTransactionCount", block_number)
+            .call(|client| client.transaction_count(address, pinned))
             .await
-            .map_err(to_error_object)
     }
     async fn get_code(&self, address: Address, block_number: Option<BlockId>) -> RpcResult<Bytes> {
-        self.backend_eth_api()
-            .get_code(address, block_number)
-            .await
-            .map_err(to_error_object)
+        let pinned = self.pin_block_id(block_number);
+        let lifetime = match pinned {
+            Some(BlockId::Hash(_)) => Some(Lifetime::Forever),
+            _ => None,
+        };
+        self.cached("eth_getCode", (address, pinned), lifetime, async {
+            self.route("eth_getCode", block_number)
+                .call(|client| client.get_code(address, pinned))
+                .await
+        })
+        .await
     }
     async fn header_by_number(&self, hash: BlockNumberOrTag) -> RpcResult<Option<Header>> {
-        self.backend_eth_api()
-            .header_by_number(hash)
+        let pinned = self.pin_block_number(hash);
+        self.route("eth_getHeaderByNumber", Some(hash.into()))
+            .call(|client| client.header_by_number(pinned))
             .await
-            .map_err(to_error_object)
     }
     async fn header_by_hash(&self, hash: B256) -> RpcResult<Option<Header>> {
-        self.backend_eth_api()
-            .header_by_hash(hash)
+        self.route("eth_getHeaderByHash", None)
+            .call(|client| client.header_by_hash(hash))
             .await
-            .map_err(to_error_object)
     }
     async fn call(
         &self,
@@ -245,10 +407,21 @@ impl EthApiServer for Api {
         state_overrides: Option<StateOverride>,
         block_overrides: Option<Box<BlockOverrides>>,
     ) -> RpcResult<Bytes> {
-        self.backend_eth_api()
-            .call(request, block_number, state_overrides, block_overrides)
-            .await
-            .map_err(to_error_object)
+        let pinned = self.pin_block_id(block_number);
+        let params = (&request, pinned, &state_overrides, &block_overrides);
+        self.coalesced("eth_call", params, async {
+            self.route("eth_call", block_number)
+                .call(|client| {
+                    client.call(
+                        request.clone(),
+                        pinned,
+                        state_overrides.clone(),
+                        block_overrides.clone(),
+                    )
+                })
+                .await
+        })
+        .await
     }
     async fn call_many(
         &self,
@@ -256,20 +429,37 @@ impl EthApiServer for Api {
         state_context: Option<StateContext>,
         state_override: Option<StateOverride>,
     ) -> RpcResult<Vec<EthCallResponse>> {
-        self.backend_eth_api()
-            .call_many(bundle, state_context, state_override)
+        let block_number = state_context
+            .as_ref()
+            .and_then(|context| context.block_number);
+        let pinned = self.pin_block_id(block_number);
+        let state_context = if pinned != block_number {
+            Some(StateContext {
+                block_number: pinned,
+                ..state_context.unwrap_or_default()
+            })
+        } else {
+            state_context
+        };
+        self.route("eth_callMany", block_number)
+            .call(|client| {
+                client.call_many(
+                    bundle.clone(),
+                    state_context.clone(),
+                    state_override.clone(),
+                )
+            })
             .await
-            .map_err(to_error_object)
     }
     async fn create_access_list(
         &self,
         request: TransactionRequest,
         block_number: Option<BlockId>,
     ) -> RpcResult<AccessListWithGasUsed> {
-        self.backend_eth_api()
-            .create_access_list(request, block_number)
+        let pinned = self.pin_block_id(block_number);
+        self.route("eth_createAccessList", block_number)
+            .call(|client| client.create_access_list(request.clone(), pinned))
             .await
-            .map_err(to_error_object)
     }
     async fn estimate_gas(
         &self,
@@ -277,25 +467,35 @@ impl EthApiServer for Api {
         block_number: Option<BlockId>,
         state_override: Option<StateOverride>,
     ) -> RpcResult<U256> {
-        self.backend_eth_api()
-            .estimate_gas(request, block_number, state_override)
-            .await
-            .map_err(to_error_object)
+        let pinned = self.pin_block_id(block_number);
+        let params = (&request, pinned, &state_override);
+        self.coalesced("eth_estimateGas", params, async {
+            self.route("eth_estimateGas", block_number)
+                .call(|client| client.estimate_gas(request.clone(), pinned, state_override.clone()))
+                .await
+        })
+        .await
     }
     async fn gas_price(&self) -> RpcResult<U256> {
-        self.backend_eth_api().gas_price().await.map_err(to_error_object)
+        if let Some((tip, latest)) = self.suggested_tip() {
+            return Ok(U256::from(latest.next_base_fee().saturating_add(tip)));
+        }
+        self.route("eth_gasPrice", None)
+            .call(|client| client.gas_price())
+            .await
     }
     async fn max_priority_fee_per_gas(&self) -> RpcResult<U256> {
-        self.backend_eth_api()
-            .max_priority_fee_per_gas()
+        if let Some((tip, _)) = self.suggested_tip() {
+            return Ok(U256::from(tip));
+        }
+        self.route("eth_maxPriorityFeePerGas", None)
+            .call(|client| client.max_priority_fee_per_gas())
             .await
-            .map_err(to_error_object)
     }
     async fn blob_base_fee(&self) -> RpcResult<U256> {
-        self.backend_eth_api()
-            .blob_base_fee()
+        self.route("eth_blobBaseFee", None)
+            .call(|client| client.blob_base_fee())
             .await
-            .map_err(to_error_object)
     }
     async fn fee_history(
         &self,
@@ -303,61 +503,121 @@ impl EthApiServer for Api {
         newest_block: BlockNumberOrTag,
         reward_percentiles: Option<Vec<f64>>,
     ) -> RpcResult<FeeHistory> {
-        self.backend_eth_api()
-            .fee_history(block_count, newest_block, reward_percentiles)
+        let block_count: u64 = block_count.into();
+        if let Some(fee_history) =
+            self.local_fee_history(block_count, newest_block, reward_percentiles.as_deref())
+        {
+            return Ok(fee_history);
+        }
+
+        let block_count = U64HexOrNumber::from(block_count);
+        let pinned = self.pin_block_number(newest_block);
+        self.route("eth_feeHistory", Some(newest_block.into()))
+            .call(|client| client.fee_history(block_count, pinned, reward_percentiles.clone()))
             .await
-            .map_err(to_error_object)
     }
     async fn is_mining(&self) -> RpcResult<bool> {
-        self.backend_eth_api().is_mining().await.map_err(to_error_object)
+        self.route("eth_mining", None)
+            .call(|client| client.is_mining())
+            .await
     }
     async fn hashrate(&self) -> RpcResult<U256> {
-        self.backend_eth_api().hashrate().await.map_err(to_error_object)
+        self.route("eth_hashrate", None)
+            .call(|client| client.hashrate())
+            .await
     }
     async fn get_work(&self) -> RpcResult<Work> {
-        self.backend_eth_api().get_work().await.map_err(to_error_object)
+        self.route("eth_getWork", None)
+            .call(|client| client.get_work())
+            .await
     }
     async fn submit_hashrate(&self, hashrate: U256, id: B256) -> RpcResult<bool> {
-        self.backend_eth_api()
-            .submit_hashrate(hashrate, id)
+        self.route("eth_submitHashrate", None)
+            .call_primary(|client| client.submit_hashrate(hashrate, id))
             .await
-            .map_err(to_error_object)
     }
     async fn submit_work(&self, nonce: B64, pow_hash: B256, mix_digest: B256) -> RpcResult<bool> {
-        self.backend_eth_api()
-            .submit_work(nonce, pow_hash, mix_digest)
+        self.route("eth_submitWork", None)
+            .call_primary(|client| client.submit_work(nonce, pow_hash, mix_digest))
             .await
-            .map_err(to_error_object)
     }
     async fn send_transaction(&self, request: TransactionRequest) -> RpcResult<B256> {
-        self.backend_eth_api()
-            .send_transaction(request)
+        if let Some(signer) = self.signer() {
+            let (from, transaction) = self.fill_transaction(signer, request).await?;
+            let signed = signer
+                .sign_transaction(from, transaction)
+                .map_err(signer_error)?;
+            return self.send_raw_transaction(signed.envelope_encoded()).await;
+        }
+        self.route("eth_sendTransaction", None)
+            .call_primary(|client| client.send_transaction(request))
             .await
-            .map_err(to_error_object)
     }
     async fn send_raw_transaction(&self, bytes: Bytes) -> RpcResult<B256> {
-        self.backend_eth_api()
-            .send_raw_transaction(bytes)
-            .await
-            .map_err(to_error_object)
+        let transaction = recover_raw_transaction(&bytes)?;
+        if let Err(rejection) = self
+            .0
+            .admission
+            .check(transaction.signer(), transaction.to())
+        {
+            return Err(self.reject(&transaction, rejection));
+        }
+        if let Err(rejection) = self.0.miner.check(&transaction) {
+            return Err(self.reject(&transaction, rejection));
+        }
+        match self.0.rules.evaluate(&transaction) {
+            Verdict::Allow => (),
+            Verdict::Deny(rule) => {
+                return Err(self.reject(&transaction, Rejection::DeniedByRule(rule)));
+            }
+            Verdict::Deprioritize(rule) => {
+                tracing::debug!("deprioritizing {} by rule {:?}", transaction.hash(), rule);
+                metrics::counter!("sequencer_admission_deprioritized_total").increment(1);
+
+                let hash = transaction.hash();
+                self.deprioritize(transaction, bytes)?;
+                return Ok(hash);
+            }
+        }
+
+        let hash = self
+            .0
+            .router
+            .default_backend()
+            .call_primary("eth_sendRawTransaction", |client| {
+                client.send_raw_transaction(bytes.clone())
+            })
+            .await?;
+        self.accept(transaction, bytes);
+        Ok(hash)
     }
     async fn sign(&self, address: Address, message: Bytes) -> RpcResult<Bytes> {
-        self.backend_eth_api()
-            .sign(address, message)
+        if let Some(signer) = self.signer() {
+            return signer.sign_message(address, &message).map_err(signer_error);
+        }
+        self.route("eth_sign", None)
+            .call(|client| client.sign(address, message.clone()))
             .await
-            .map_err(to_error_object)
     }
     async fn sign_transaction(&self, transaction: TransactionRequest) -> RpcResult<Bytes> {
-        self.backend_eth_api()
-            .sign_transaction(transaction)
+        if let Some(signer) = self.signer() {
+            let (from, transaction) = self.fill_transaction(signer, transaction).await?;
+            let signed = signer
+                .sign_transaction(from, transaction)
+                .map_err(signer_error)?;
+            return Ok(signed.envelope_encoded());
+        }
+        self.route("eth_signTransaction", None)
+            .call(|client| client.sign_transaction(transaction.clone()))
             .await
-            .map_err(to_error_object)
     }
     async fn sign_typed_data(&self, address: Address, data: serde_json::Value) -> RpcResult<Bytes> {
-        self.backend_eth_api()
-            .sign_typed_data(address, data)
+        if let Some(signer) = self.signer() {
+            return signer.sign_typed_data(address, data).map_err(signer_error);
+        }
+        self.route("eth_signTypedData", None)
+            .call(|client| client.sign_typed_data(address, data.clone()))
             .await
-            .map_err(to_error_object)
     }
 
     async fn get_proof(
@@ -366,9 +626,9 @@ impl EthApiServer for Api {
         keys: Vec<JsonStorageKey>,
         block_number: Option<BlockId>,
     ) -> RpcResult<EIP1186AccountProofResponse> {
-        self.backend_eth_api()
-            .get_proof(address, keys, block_number)
+        let pinned = self.pin_block_id(block_number);
+        self.route("eth_getProof", block_number)
+            .call(|client| client.get_proof(address, keys.clone(), pinned))
             .await
-            .map_err(to_error_object)
     }
 }
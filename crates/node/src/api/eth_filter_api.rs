@@ -1,62 +1,286 @@
+use alloy_rpc_types::BlockId;
+use alloy_rpc_types::BlockNumberOrTag;
 use alloy_rpc_types::Filter;
+use alloy_rpc_types::FilterBlockOption;
 use alloy_rpc_types::Log;
+use futures::{StreamExt, TryStreamExt};
 use jsonrpsee::core::RpcResult;
+use jsonrpsee::types::error::INVALID_PARAMS_CODE;
+use jsonrpsee::types::ErrorObject;
 use reth_rpc_api::{EthFilterApiClient, EthFilterApiServer};
 use reth_rpc_types::{FilterChanges, FilterId, PendingTransactionFilterKind};
+use reth_rpc_types_compat::transaction::from_recovered;
 
-use super::{to_error_object, Api};
+use crate::error::ProxyError;
+use crate::filters::{Changes, FilterKind, Filters};
+use crate::public_server::current_client;
+use crate::response_cache::Lifetime;
+
+use super::{as_u128, Api};
 
 impl Api {
-    pub fn backend_eth_filter_api(&self) -> &impl EthFilterApiClient {
-        &self.0.anonymous_client
+    pub fn filters(&self) -> &Filters {
+        &self.0.filters
+    }
+
+    /// The first block not seen by a filter installed now.
+    fn next_block(&self) -> u64 {
+        self.0
+            .heads
+            .head_number()
+            .map_or(0, |head| head.saturating_add(1))
+    }
+
+    /// Installs a filter on behalf of the client calling.
+    fn install_filter(&self, kind: FilterKind) -> RpcResult<FilterId> {
+        self.0
+            .filters
+            .install(kind, self.next_block(), current_client())
+            .map_err(|max_filters| ProxyError::FiltersExceeded { max_filters }.into())
+    }
+
+    /// The logs of filter `id` in the blocks `cursor..=head`, which it is taken to have
+    /// reported on unless they fail to be fetched.
+    async fn log_changes(
+        &self,
+        id: &FilterId,
+        filter: Filter,
+        cursor: u64,
+        head: u64,
+    ) -> RpcResult<Vec<Log>> {
+        let FilterBlockOption::Range {
+            from_block,
+            to_block,
+        } = filter.block_option
+        else {
+            return Ok(vec![]);
+        };
+        let from = cursor.max(block_number(from_block).unwrap_or_default());
+        let to = head.min(block_number(to_block).unwrap_or(u64::MAX));
+        if from > to {
+            return Ok(vec![]);
+        }
+
+        let logs = self.logs(filter.from_block(from).to_block(to)).await;
+        if logs.is_err() {
+            self.0.filters.rewind(id, cursor, head);
+        }
+        logs
+    }
+
+    fn current_head(&self) -> u64 {
+        self.0
+            .current_block_number
+            .read()
+            .expect("rw-lock.read -> poisoned")
+            .saturating_to()
+    }
+
+    async fn fetch_logs(&self, filter: Filter) -> RpcResult<Vec<Log>> {
+        let from_block = match filter.block_option {
+            FilterBlockOption::Range { from_block, .. } => from_block.map(BlockId::from),
+            FilterBlockOption::AtBlockHash(hash) => Some(BlockId::from(hash)),
+        };
+        self.route("eth_getLogs", from_block)
+            .call(|client| client.logs(filter.clone()))
+            .await
+    }
+
+    fn check_logs_count(&self, logs: Vec<Log>) -> RpcResult<Vec<Log>> {
+        match self.1.logs_limits.max_results {
+            Some(max_results) if logs.len() > max_results => {
+                Err(ProxyError::LogsResultsExceeded { max_results }.into())
+            }
+            _ => Ok(logs),
+        }
     }
 }
 
 #[async_trait::async_trait]
 impl EthFilterApiServer for Api {
     async fn new_filter(&self, filter: Filter) -> RpcResult<FilterId> {
-        self.backend_eth_filter_api()
-            .new_filter(filter)
-            .await
-            .map_err(to_error_object)
+        self.install_filter(FilterKind::Logs(filter))
     }
     async fn new_block_filter(&self) -> RpcResult<FilterId> {
-        self.backend_eth_filter_api()
-            .new_block_filter()
-            .await
-            .map_err(to_error_object)
+        self.install_filter(FilterKind::Blocks)
     }
     async fn new_pending_transaction_filter(
         &self,
         kind: Option<PendingTransactionFilterKind>,
     ) -> RpcResult<FilterId> {
-        self.backend_eth_filter_api()
-            .new_pending_transaction_filter(kind)
-            .await
-            .map_err(to_error_object)
+        let kind = FilterKind::PendingTransactions {
+            full: matches!(kind, Some(PendingTransactionFilterKind::Full)),
+            accepted: self.0.pending_transactions.subscribe(),
+        };
+        self.install_filter(kind)
     }
     async fn filter_changes(&self, id: FilterId) -> RpcResult<FilterChanges> {
-        self.backend_eth_filter_api()
-            .filter_changes(id)
-            .await
-            .map_err(to_error_object)
+        let head = self.0.heads.head_number();
+        let changes = self
+            .0
+            .filters
+            .changes(&id, head)
+            .ok_or_else(filter_not_found)?;
+        match changes {
+            Changes::Logs { to: None, .. } => Ok(FilterChanges::Logs(vec![])),
+            Changes::Logs {
+                filter,
+                from,
+                to: Some(to),
+            } => Ok(FilterChanges::Logs(
+                self.log_changes(&id, filter, from, to).await?,
+            )),
+            Changes::Blocks { to: None, .. } => Ok(FilterChanges::Hashes(vec![])),
+            Changes::Blocks { from, to: Some(to) } => Ok(FilterChanges::Hashes(
+                self.0
+                    .heads
+                    .headers_from(from)
+                    .into_iter()
+                    .take_while(|header| {
+                        header
+                            .number
+                            .is_some_and(|number| as_u128(number) as u64 <= to)
+                    })
+                    .filter_map(|header| header.hash)
+                    .collect(),
+            )),
+            Changes::PendingTransactions {
+                full: true,
+                transactions,
+            } => Ok(FilterChanges::Transactions(
+                transactions.into_iter().map(from_recovered).collect(),
+            )),
+            Changes::PendingTransactions {
+                full: false,
+                transactions,
+            } => Ok(FilterChanges::Hashes(
+                transactions.iter().map(|tx| tx.hash()).collect(),
+            )),
+        }
     }
     async fn filter_logs(&self, id: FilterId) -> RpcResult<Vec<Log>> {
-        self.backend_eth_filter_api()
-            .filter_logs(id)
-            .await
-            .map_err(to_error_object)
+        let filter = self
+            .0
+            .filters
+            .logs_filter(&id)
+            .ok_or_else(filter_not_found)?;
+        self.logs(filter).await
     }
     async fn uninstall_filter(&self, id: FilterId) -> RpcResult<bool> {
-        self.backend_eth_filter_api()
-            .uninstall_filter(id)
-            .await
-            .map_err(to_error_object)
+        Ok(self.0.filters.uninstall(&id))
     }
     async fn logs(&self, filter: Filter) -> RpcResult<Vec<Log>> {
-        self.backend_eth_filter_api()
-            .logs(filter)
-            .await
-            .map_err(to_error_object)
+        let FilterBlockOption::Range {
+            from_block,
+            to_block,
+        } = filter.block_option
+        else {
+            let logs = self.fetch_logs(filter).await?;
+            return self.check_logs_count(logs);
+        };
+        let head = self.current_head();
+        let finalized = self.0.response_cache.finalized();
+        let (from, requested_to) = (
+            resolve_block(from_block, head, finalized),
+            resolve_block(to_block, head, finalized),
+        );
+        if from > requested_to {
+            return self.fetch_logs(filter).await;
+        }
+        if head > 0 && from > head {
+            return Err(ErrorObject::owned(
+                INVALID_PARAMS_CODE,
+                format!("fromBlock {} is beyond the head {}", from, head),
+                None::<()>,
+            ));
+        }
+        // No logs are found past the head: the range only spans the blocks up to it.
+        let to = match head {
+            0 => requested_to,
+            head => requested_to.min(head),
+        };
+
+        let limits = self.1.logs_limits;
+        if let Some(max_block_range) = limits.max_block_range {
+            let block_range = (to - from).saturating_add(1);
+            if block_range > max_block_range {
+                return Err(ProxyError::LogsBlockRangeExceeded {
+                    block_range,
+                    max_block_range,
+                }
+                .into());
+            }
+        }
+
+        let pin = |block: Option<BlockNumberOrTag>, number: u64| match block {
+            None | Some(BlockNumberOrTag::Latest) if self.1.pin_latest => {
+                Some(BlockNumberOrTag::Number(number))
+            }
+            _ => block,
+        };
+        let filter = Filter {
+            block_option: FilterBlockOption::Range {
+                from_block: pin(from_block, from),
+                to_block: if requested_to > to {
+                    Some(BlockNumberOrTag::Number(to))
+                } else {
+                    pin(to_block, to)
+                },
+            },
+            ..filter
+        };
+
+        // Without a head to bound it, the range is not split.
+        let chunks = match head {
+            0 => vec![(from, to)],
+            _ => self.0.logs_chunking.chunks(from, to),
+        };
+        if chunks.len() == 1 {
+            let logs = self.fetch_logs(filter).await?;
+            return self.check_logs_count(logs);
+        }
+        let chunks = futures::stream::iter(chunks)
+            .map(|(start, end)| {
+                let chunk = filter.clone().from_block(start).to_block(end);
+                // Only the chunks that cannot be reorged are cached, even without logs.
+                let lifetime = (end <= finalized).then_some(Lifetime::Forever);
+                async move {
+                    self.cached_even_if_empty(
+                        "eth_getLogs",
+                        &chunk,
+                        lifetime,
+                        self.fetch_logs(chunk.clone()),
+                    )
+                    .await
+                }
+            })
+            .buffered(self.0.logs_chunking.parallelism.max(1))
+            .try_collect::<Vec<_>>()
+            .await?;
+        self.check_logs_count(chunks.into_iter().flatten().collect())
     }
 }
+
+/// The number `block` refers to, given the `head` and the `finalized` block;
+/// a missing block means the head.
+fn resolve_block(block: Option<BlockNumberOrTag>, head: u64, finalized: u64) -> u64 {
+    match block {
+        Some(BlockNumberOrTag::Number(number)) => number,
+        Some(BlockNumberOrTag::Earliest) => 0,
+        Some(BlockNumberOrTag::Finalized) if finalized > 0 => finalized,
+        _ => head,
+    }
+}
+
+/// The number of an explicit block; `None` for the tags that follow the head.
+fn block_number(block: Option<BlockNumberOrTag>) -> Option<u64> {
+    match block? {
+        BlockNumberOrTag::Number(number) => Some(number),
+        BlockNumberOrTag::Earliest => Some(0),
+        _ => None,
+    }
+}
+
+fn filter_not_found() -> jsonrpsee::types::ErrorObjectOwned {
+    ProxyError::FilterNotFound.into()
+}
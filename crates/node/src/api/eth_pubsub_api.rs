@@ -0,0 +1,242 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use alloy_primitives::B256;
+use alloy_rpc_types::{Filter, FilteredParams, Header, Log};
+use jsonrpsee::core::SubscriptionResult;
+use jsonrpsee::types::error::INVALID_PARAMS_CODE;
+use jsonrpsee::types::ErrorObject;
+use jsonrpsee::{PendingSubscriptionSink, SubscriptionMessage, SubscriptionSink};
+use reth_primitives::TransactionSignedEcRecovered;
+use reth_rpc_api::{EthFilterApiClient, EthPubSubApiServer};
+use reth_rpc_types::pubsub::{Params, SubscriptionKind};
+use reth_rpc_types_compat::transaction::from_recovered;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::head_tracker::HEADER_CHAIN_DEPTH;
+use crate::AnyError;
+
+use super::{as_u128, Api};
+
+/// The number of accepted transactions buffered for each of the subscribers.
+pub(super) const PENDING_TRANSACTIONS_CAPACITY: usize = 1024;
+
+/// The number of blocks' logs buffered for each of the subscribers.
+const BLOCK_LOGS_CAPACITY: usize = 64;
+
+/// The logs of each new block, fetched once for all the subscribers.
+#[derive(Debug)]
+pub(super) struct LogsFeed {
+    logs: broadcast::Sender<Arc<Vec<Log>>>,
+    /// The logs sent for the recent blocks, by number, along with the block hash:
+    /// they are sent again as removed if their block is reorged out.
+    recent: Mutex<BTreeMap<u64, (B256, Arc<Vec<Log>>)>>,
+}
+
+impl Default for LogsFeed {
+    fn default() -> Self {
+        Self {
+            logs: broadcast::channel(BLOCK_LOGS_CAPACITY).0,
+            recent: Default::default(),
+        }
+    }
+}
+
+/// Serves the subscriptions from the sequencer's own head tracking and intake,
+/// rather than from a backend subscription per client.
+#[async_trait::async_trait]
+impl EthPubSubApiServer for Api {
+    async fn subscribe(
+        &self,
+        pending: PendingSubscriptionSink,
+        kind: SubscriptionKind,
+        params: Option<Params>,
+    ) -> SubscriptionResult {
+        match (kind, params) {
+            (SubscriptionKind::NewHeads, None) => {
+                let sink = pending.accept().await?;
+                forward(sink, self.0.heads.subscribe(), |header: Header| async move {
+                    Ok(vec![SubscriptionMessage::from_json(&header)?])
+                })
+                .await
+            }
+            (SubscriptionKind::Logs, None) => self.subscribe_logs(pending, Filter::default()).await,
+            (SubscriptionKind::Logs, Some(Params::Logs(filter))) => {
+                self.subscribe_logs(pending, *filter).await
+            }
+            (SubscriptionKind::NewPendingTransactions, None) => {
+                self.subscribe_pending_transactions(pending, false).await
+            }
+            (SubscriptionKind::NewPendingTransactions, Some(Params::Bool(full))) => {
+                self.subscribe_pending_transactions(pending, full).await
+            }
+            (kind, _) => {
+                pending
+                    .reject(ErrorObject::owned(
+                        INVALID_PARAMS_CODE,
+                        format!("unsupported subscription {:?} or its params", kind),
+                        None::<()>,
+                    ))
+                    .await;
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Api {
+    async fn subscribe_logs(
+        &self,
+        pending: PendingSubscriptionSink,
+        filter: Filter,
+    ) -> SubscriptionResult {
+        let sink = pending.accept().await?;
+        let params = FilteredParams::new(Some(filter));
+        forward(
+            sink,
+            self.0.logs_feed.logs.subscribe(),
+            |logs: Arc<Vec<Log>>| {
+                let messages = logs
+                    .iter()
+                    .filter(|log| {
+                        params.filter_address(&log.address()) && params.filter_topics(log.topics())
+                    })
+                    .map(SubscriptionMessage::from_json)
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(AnyError::from);
+                async move { messages }
+            },
+        )
+        .await
+    }
+
+    /// Fetches the logs of each new head, and sends them to the logs subscribers; those of
+    /// the blocks reorged out are sent again, as removed.
+    pub async fn follow_logs(&self) {
+        let mut heads = self.0.heads.subscribe();
+        loop {
+            let header = match heads.recv().await {
+                Ok(header) => header,
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::debug!("logs feed skipped {} heads", skipped);
+                    continue;
+                }
+                Err(RecvError::Closed) => return,
+            };
+            let (Some(number), Some(hash)) = (header.number, header.hash) else {
+                continue;
+            };
+            if let Err(reason) = self.feed_logs(as_u128(number) as u64, hash).await {
+                tracing::warn!("failed to fetch the logs of {}: {}", hash, reason);
+            }
+        }
+    }
+
+    async fn feed_logs(&self, number: u64, hash: B256) -> Result<(), AnyError> {
+        let feed = &self.0.logs_feed;
+        let removed = {
+            let mut recent = feed.recent.lock().expect("mutex.lock -> poisoned");
+            if recent.get(&number).is_some_and(|(sent, _)| *sent == hash) {
+                return Ok(());
+            }
+            recent.split_off(&number)
+        };
+        for (_, (_, logs)) in removed.into_iter().rev() {
+            let removed = logs
+                .iter()
+                .cloned()
+                .map(|log| Log {
+                    removed: true,
+                    ..log
+                })
+                .collect();
+            let _ = feed.logs.send(Arc::new(removed));
+        }
+        if feed.logs.receiver_count() == 0 {
+            return Ok(());
+        }
+
+        let logs = self
+            .route("eth_getLogs", None)
+            .call(|client| client.logs(Filter::new().at_block_hash(hash)))
+            .await?;
+        let logs = Arc::new(logs);
+        {
+            let mut recent = feed.recent.lock().expect("mutex.lock -> poisoned");
+            recent.insert(number, (hash, logs.clone()));
+            let oldest_kept = number.saturating_sub(HEADER_CHAIN_DEPTH - 1);
+            *recent = recent.split_off(&oldest_kept);
+        }
+        let _ = feed.logs.send(logs);
+        Ok(())
+    }
+
+    /// Sends the hashes, or the full transactions, accepted by the sequencer.
+    async fn subscribe_pending_transactions(
+        &self,
+        pending: PendingSubscriptionSink,
+        full: bool,
+    ) -> SubscriptionResult {
+        let sink = pending.accept().await?;
+        forward(
+            sink,
+            self.0.pending_transactions.subscribe(),
+            |transaction: TransactionSignedEcRecovered| async move {
+                let message = if full {
+                    SubscriptionMessage::from_json(&from_recovered(transaction))?
+                } else {
+                    SubscriptionMessage::from_json(&transaction.hash())?
+                };
+                Ok(vec![message])
+            },
+        )
+        .await
+    }
+}
+
+/// Sends the messages made of each of the `items` to `sink` until the subscriber leaves.
+async fn forward<T, F, Fut>(
+    sink: SubscriptionSink,
+    mut items: broadcast::Receiver<T>,
+    mut messages: F,
+) -> SubscriptionResult
+where
+    T: Clone,
+    F: FnMut(T) -> Fut,
+    Fut: Future<Output = Result<Vec<SubscriptionMessage>, AnyError>>,
+{
+    loop {
+        let item = tokio::select! {
+            () = sink.closed() => return Ok(()),
+            item = items.recv() => item,
+        };
+        let item = match item {
+            Ok(item) => item,
+            Err(RecvError::Lagged(skipped)) => {
+                tracing::debug!(
+                    "subscription {:?} skipped {} items",
+                    sink.subscription_id(),
+                    skipped
+                );
+                continue;
+            }
+            Err(RecvError::Closed) => return Ok(()),
+        };
+        match messages(item).await {
+            Ok(messages) => {
+                for message in messages {
+                    if sink.send(message).await.is_err() {
+                        return Ok(());
+                    }
+                }
+            }
+            Err(reason) => tracing::warn!(
+                "subscription {:?} failed to get an item: {}",
+                sink.subscription_id(),
+                reason
+            ),
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+
+use alloy_primitives::Address;
+use alloy_rpc_types::{BlockNumberOrTag, BlockTransactions, Transaction};
+use reth_primitives::TransactionSignedEcRecovered;
+use reth_rpc_api::EthApiClient;
+
+use crate::gas_oracle::{BlockFees, GasOracle};
+use crate::AnyError;
+
+use super::{as_u128, Api};
+
+/// The OP Stack deposit transaction type; deposits pay no tips.
+const DEPOSIT_TX_TYPE: u128 = 0x7e;
+
+impl Api {
+    pub fn gas_oracle(&self) -> &GasOracle {
+        &self.0.gas_oracle
+    }
+
+    /// Fetches block `number` from the backend and records its fees in the gas-oracle; the
+    /// pooled transactions it includes are removed first, as they no longer compete for a block.
+    pub async fn observe_block(&self, number: u64) -> Result<(), AnyError> {
+        let Some(block) = self
+            .backend_eth_api()
+            .block_by_number(BlockNumberOrTag::Number(number), true)
+            .await?
+        else {
+            return Ok(());
+        };
+
+        let base_fee = block
+            .header
+            .base_fee_per_gas
+            .map(as_u128)
+            .unwrap_or_default();
+        let tips = match &block.transactions {
+            BlockTransactions::Full(transactions) => transactions
+                .iter()
+                .filter(|tx| tx.transaction_type.map(as_u128) != Some(DEPOSIT_TX_TYPE))
+                .map(|tx| (rpc_tip(tx, base_fee), as_u128(tx.gas) as u64))
+                .collect(),
+            _ => vec![],
+        };
+        if let BlockTransactions::Full(transactions) = &block.transactions {
+            self.remove_included_in(transactions);
+        }
+
+        self.0.gas_oracle.observe(BlockFees {
+            number,
+            base_fee,
+            gas_used: as_u128(block.header.gas_used),
+            gas_limit: as_u128(block.header.gas_limit),
+            tips,
+        });
+        Ok(())
+    }
+
+    /// The tip suggested by the recent blocks, raised to outbid the pool
+    /// if it holds more executable transactions than usually fit into a block.
+    pub(super) fn suggested_tip(&self) -> Option<(u128, BlockFees)> {
+        let latest = self.0.gas_oracle.latest()?;
+        let tip = self
+            .0
+            .gas_oracle
+            .suggested_tip()?
+            .max(self.0.miner.min_tip().unwrap_or_default());
+
+        let cutoff = self.pool_tip_cutoff(&latest);
+        Some((tip.max(cutoff.unwrap_or_default()), latest))
+    }
+
+    /// The tip outbidding the executable transactions in the pool, if it holds more of them
+    /// than usually fit into a block; computed once per block.
+    fn pool_tip_cutoff(&self, latest: &BlockFees) -> Option<u128> {
+        if let Some((cached_at, cutoff)) = *self
+            .0
+            .pool_tip_cutoff
+            .read()
+            .expect("rw-lock.read -> poisoned")
+        {
+            if cached_at == latest.number {
+                return cutoff;
+            }
+        }
+
+        let capacity = self.0.gas_oracle.transactions_per_block().max(1);
+        let next_base_fee = latest.next_base_fee();
+        let mut pool_tips = self
+            .0
+            .pool
+            .content()
+            .pending
+            .into_values()
+            .flatten()
+            .map(|pooled| pool_tip(&pooled.transaction, next_base_fee))
+            .collect::<Vec<_>>();
+        let cutoff = (pool_tips.len() >= capacity).then(|| {
+            pool_tips.sort_unstable_by(|a, b| b.cmp(a));
+            pool_tips[capacity - 1].saturating_add(1)
+        });
+
+        *self
+            .0
+            .pool_tip_cutoff
+            .write()
+            .expect("rw-lock.write -> poisoned") = Some((latest.number, cutoff));
+        cutoff
+    }
+
+    /// Removes the pooled transactions that `transactions` include or replace, rather than
+    /// waiting for the pool to be pruned.
+    fn remove_included_in(&self, transactions: &[Transaction]) {
+        let mut next_nonces = HashMap::<Address, u64>::new();
+        for tx in transactions
+            .iter()
+            .filter(|tx| tx.transaction_type.map(as_u128) != Some(DEPOSIT_TX_TYPE))
+        {
+            let next_nonce = (as_u128(tx.nonce) as u64).saturating_add(1);
+            let known = next_nonces.entry(tx.from).or_default();
+            *known = next_nonce.max(*known);
+        }
+        for (sender, next_nonce) in next_nonces {
+            self.0.pool.remove_included(sender, next_nonce);
+        }
+    }
+}
+
+fn rpc_tip(tx: &Transaction, base_fee: u128) -> u128 {
+    let max_fee = tx
+        .max_fee_per_gas
+        .or(tx.gas_price)
+        .map(as_u128)
+        .unwrap_or_default();
+    let max_tip = tx.max_priority_fee_per_gas.map(as_u128).unwrap_or(max_fee);
+    max_tip.min(max_fee.saturating_sub(base_fee))
+}
+
+fn pool_tip(tx: &TransactionSignedEcRecovered, base_fee: u128) -> u128 {
+    tx.priority_fee_or_price()
+        .min(tx.max_fee_per_gas().saturating_sub(base_fee))
+}
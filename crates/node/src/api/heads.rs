@@ -0,0 +1,94 @@
+use std::time::Duration;
+
+use alloy_primitives::U256;
+use alloy_rpc_types::{BlockNumberOrTag, Header};
+use futures::StreamExt;
+use jsonrpsee::core::client::{Subscription, SubscriptionClientT};
+use jsonrpsee::rpc_params;
+use jsonrpsee::ws_client::WsClientBuilder;
+use reth_rpc_api::EthApiClient;
+
+use crate::head_tracker::{HeadTracker, Link, HEADER_CHAIN_DEPTH};
+use crate::AnyError;
+
+use super::{as_u128, Api};
+
+/// How long to wait before subscribing again after the subscription failed.
+const RESUBSCRIBE_DELAY: Duration = Duration::from_secs(1);
+
+impl Api {
+    pub fn heads(&self) -> &HeadTracker {
+        &self.0.heads
+    }
+
+    /// Makes `header` the head, fetching the headers linking it to the tracked chain first.
+    pub async fn apply_head(&self, header: Header) -> Result<(), AnyError> {
+        let mut chain = vec![header];
+        while (chain.len() as u64) < HEADER_CHAIN_DEPTH {
+            let oldest = chain.last().expect("not empty");
+            let Some(number) = oldest.number.map(|n| as_u128(n) as u64) else {
+                return Err("header without a number".into());
+            };
+            let Link::MissingParent(parent_hash) = self.0.heads.link(number, oldest.parent_hash)
+            else {
+                break;
+            };
+            let Some(parent) = self.backend_eth_api().header_by_hash(parent_hash).await? else {
+                break;
+            };
+            chain.push(parent);
+        }
+
+        for header in chain.into_iter().rev() {
+            let (Some(number), Some(hash)) = (header.number.map(as_u128), header.hash) else {
+                continue;
+            };
+            let number = number as u64;
+            self.0.heads.apply(number, header);
+            self.0.response_cache.observe_canonical(number, hash);
+        }
+        if let Some(head) = self.0.heads.head_number() {
+            self.set_current_block_number(U256::from(head));
+        }
+        Ok(())
+    }
+
+    /// Fetches header `number` from the backend and makes it the head.
+    pub async fn fetch_head(&self, number: u64) -> Result<(), AnyError> {
+        let header = self
+            .backend_eth_api()
+            .header_by_number(BlockNumberOrTag::Number(number))
+            .await?;
+        if let Some(header) = header {
+            self.apply_head(header).await?;
+        }
+        Ok(())
+    }
+
+    /// Applies the heads pushed by the `newHeads` subscription at `ws_url`; resubscribes on failure.
+    pub async fn follow_new_heads(&self, ws_url: &str) {
+        loop {
+            if let Err(reason) = self.follow_new_heads_once(ws_url).await {
+                tracing::warn!("newHeads subscription at {} failed: {}", ws_url, reason);
+            }
+            self.0.heads.set_pushed(false);
+            tokio::time::sleep(RESUBSCRIBE_DELAY).await;
+        }
+    }
+
+    async fn follow_new_heads_once(&self, ws_url: &str) -> Result<(), AnyError> {
+        let client = WsClientBuilder::default().build(ws_url).await?;
+        let mut new_heads: Subscription<Header> = client
+            .subscribe("eth_subscribe", rpc_params!["newHeads"], "eth_unsubscribe")
+            .await?;
+        tracing::info!("subscribed to newHeads at {}", ws_url);
+        self.0.heads.set_pushed(true);
+
+        while let Some(header) = new_heads.next().await {
+            if let Err(reason) = self.apply_head(header?).await {
+                tracing::warn!("failed to apply a new head: {}", reason);
+            }
+        }
+        Err("the subscription has been closed".into())
+    }
+}
@@ -0,0 +1,187 @@
+use alloy_primitives::{Bytes, U128, U256, U64};
+use alloy_rpc_types::{BlockId, BlockNumberOrTag};
+use alloy_rpc_types_engine::ForkchoiceState;
+use jsonrpsee::core::RpcResult;
+use jsonrpsee::types::error::{INTERNAL_ERROR_CODE, INVALID_PARAMS_CODE};
+use jsonrpsee::types::ErrorObject;
+use reth_node_api::EngineTypes;
+use reth_node_optimism::OptimismEngineTypes;
+use reth_primitives::TransactionSigned;
+use reth_rpc_api::EthApiClient;
+
+use crate::gas_oracle::BlockFees;
+use crate::miner::{BlockSpace, Miner, MinerParams};
+use crate::AnyError;
+
+use super::{as_u128, Api, MinerApiServer};
+
+type PayloadAttributes = <OptimismEngineTypes as EngineTypes>::PayloadAttributes;
+
+impl Api {
+    pub fn miner(&self) -> &Miner {
+        &self.0.miner
+    }
+
+    /// If the sequencer picks the transactions itself, appends the pool's transactions
+    /// to those forced by the rollup-node and tells the backend not to use its own pool.
+    ///
+    /// Should that fail, the attributes are passed on as they are.
+    pub(super) async fn shape_payload_attributes(
+        &self,
+        fork_choice_state: &ForkchoiceState,
+        payload_attributes: Option<PayloadAttributes>,
+    ) -> Option<PayloadAttributes> {
+        let attributes = payload_attributes?;
+        if attributes.no_tx_pool == Some(true) || !self.0.miner.shapes_payloads() {
+            return Some(attributes);
+        }
+        match self
+            .fill_payload_attributes(fork_choice_state, attributes.clone())
+            .await
+        {
+            Ok(filled) => Some(filled),
+            Err(reason) => {
+                tracing::warn!("failed to pick the payload transactions: {}", reason);
+                Some(attributes)
+            }
+        }
+    }
+
+    async fn fill_payload_attributes(
+        &self,
+        fork_choice_state: &ForkchoiceState,
+        mut attributes: PayloadAttributes,
+    ) -> Result<PayloadAttributes, AnyError> {
+        // The transactions the parent includes are not left to take up the block's space.
+        let (parent, ()) = futures::try_join!(
+            async {
+                self.backend_eth_api()
+                    .block_by_hash(fork_choice_state.head_block_hash, false)
+                    .await
+                    .map_err(AnyError::from)
+            },
+            self.remove_included_at(BlockId::from(fork_choice_state.head_block_hash)),
+        )?;
+        let parent = parent.ok_or_else(|| {
+            format!(
+                "parent block {} not found",
+                fork_choice_state.head_block_hash
+            )
+        })?;
+        let parent_fees = BlockFees {
+            number: 0,
+            base_fee: parent
+                .header
+                .base_fee_per_gas
+                .map(as_u128)
+                .unwrap_or_default(),
+            gas_used: as_u128(parent.header.gas_used),
+            gas_limit: as_u128(parent.header.gas_limit),
+            tips: vec![],
+        };
+
+        let mut transactions = attributes.transactions.take().unwrap_or_default();
+        let forced_gas = transactions
+            .iter()
+            .filter_map(|raw| TransactionSigned::decode_enveloped(&mut raw.as_ref()).ok())
+            .map(|transaction| transaction.gas_limit())
+            .sum::<u64>();
+        let gas_limit = attributes.gas_limit.unwrap_or(parent_fees.gas_limit as u64);
+        let space = BlockSpace {
+            gas: gas_limit.saturating_sub(forced_gas),
+            base_fee: parent_fees.next_base_fee(),
+        };
+
+        let selected = self.0.miner.select(self.0.pool.content().pending, space);
+        tracing::debug!(
+            "shaping payload on top of {}: {} forced, {} selected transactions",
+            fork_choice_state.head_block_hash,
+            transactions.len(),
+            selected.len()
+        );
+        transactions.extend(selected);
+
+        attributes.transactions = Some(transactions);
+        attributes.no_tx_pool = Some(true);
+        Ok(attributes)
+    }
+}
+
+#[async_trait::async_trait]
+impl MinerApiServer for Api {
+    async fn set_max_da_size(&self, max_tx_size: U64, max_block_size: U64) -> RpcResult<bool> {
+        self.0
+            .miner
+            .set_max_da_size(max_tx_size.to(), max_block_size.to());
+        Ok(true)
+    }
+
+    async fn set_gas_price(&self, gas_price: U256) -> RpcResult<bool> {
+        use reth_rpc_api::MinerApiClient;
+
+        // Applied by the backend's payload builder, which keeps picking from its own pool. Zero,
+        // no limit here, is sent as is: to the backend's miner it is a floor of zero, that is no
+        // limit either, rather than whatever floor it had.
+        let backend_gas_price = U128::from(gas_price.saturating_to::<u128>());
+        self.0
+            .router
+            .default_backend()
+            .call_primary("miner_setGasPrice", |client| {
+                MinerApiClient::set_gas_price(client, backend_gas_price)
+            })
+            .await?;
+        self.0.miner.set_gas_price(gas_price);
+        Ok(true)
+    }
+
+    async fn set_gas_limit(&self, gas_limit: U64) -> RpcResult<bool> {
+        use reth_rpc_api::MinerApiClient;
+
+        // No limit here is the block gas limit there: the backend's miner takes zero literally.
+        let backend_gas_limit = match gas_limit.to::<u64>() {
+            0 => {
+                let latest = self
+                    .route("eth_getBlockByNumber", None)
+                    .call(|client| client.block_by_number(BlockNumberOrTag::Latest, false))
+                    .await?
+                    .ok_or_else(|| {
+                        ErrorObject::owned(INTERNAL_ERROR_CODE, "no latest block", None::<()>)
+                    })?;
+                U128::from(as_u128(latest.header.gas_limit))
+            }
+            gas_limit => U128::from(gas_limit),
+        };
+        self.0
+            .router
+            .default_backend()
+            .call_primary("miner_setGasLimit", |client| {
+                MinerApiClient::set_gas_limit(client, backend_gas_limit)
+            })
+            .await?;
+        self.0.miner.set_gas_limit(gas_limit.to());
+        Ok(true)
+    }
+
+    async fn set_extra(&self, extra: Bytes) -> RpcResult<bool> {
+        use reth_rpc_api::MinerApiClient;
+
+        self.0
+            .miner
+            .check_extra(&extra)
+            .map_err(|reason| ErrorObject::owned(INVALID_PARAMS_CODE, reason, None::<()>))?;
+        // The Engine API cannot carry the extra-data: it is up to the backend's payload builder.
+        self.0
+            .router
+            .default_backend()
+            .call_primary("miner_setExtra", |client| {
+                MinerApiClient::set_extra(client, extra.clone())
+            })
+            .await?;
+        self.0.miner.set_extra(extra);
+        Ok(true)
+    }
+
+    async fn get_params(&self) -> RpcResult<MinerParams> {
+        Ok(self.0.miner.params())
+    }
+}
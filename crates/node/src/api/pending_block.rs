@@ -0,0 +1,236 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use alloy_primitives::{Address, Bloom, Bytes, B256};
+use alloy_rpc_types::{BlockNumberOrTag, RichBlock, Transaction};
+use alloy_rpc_types_engine::{ForkchoiceState, ForkchoiceUpdated};
+use reth_node_api::EngineTypes;
+use reth_node_optimism::OptimismEngineTypes;
+use reth_primitives::constants::EMPTY_OMMER_ROOT_HASH;
+use reth_primitives::{TransactionSigned, TransactionSignedEcRecovered};
+use reth_rpc_types_compat::transaction::from_recovered;
+
+use crate::gas_oracle::BlockFees;
+use crate::miner::BlockSpace;
+
+use super::{as_u128, Api};
+
+type PayloadAttributes = <OptimismEngineTypes as EngineTypes>::PayloadAttributes;
+
+/// The payload the backend has last been asked to build.
+#[derive(Debug, Clone)]
+pub(super) struct PendingPayload {
+    parent_hash: B256,
+    timestamp: u64,
+    fee_recipient: Address,
+    prev_randao: B256,
+    gas_limit: Option<u64>,
+    transactions: Vec<TransactionSignedEcRecovered>,
+    /// Whether `transactions` are all of the payload's, or only those forced by the rollup-node.
+    complete: bool,
+}
+
+/// The block the sequencer is about to propose, as far as it knows.
+#[derive(Debug, Clone)]
+pub(super) struct PendingBlock {
+    pub transactions: Vec<TransactionSignedEcRecovered>,
+    block: serde_json::Value,
+}
+
+impl Api {
+    /// Records the payload the backend builds on top of `fork_choice_state`, if it builds one.
+    pub(super) fn record_pending_payload(
+        &self,
+        fork_choice_state: &ForkchoiceState,
+        updated: &ForkchoiceUpdated,
+        attributes: Option<PayloadAttributes>,
+    ) {
+        let (Some(attributes), Some(_)) = (attributes, updated.payload_id) else {
+            return;
+        };
+        let transactions = attributes
+            .transactions
+            .iter()
+            .flatten()
+            .filter_map(recover)
+            .collect();
+        let payload = PendingPayload {
+            parent_hash: fork_choice_state.head_block_hash,
+            timestamp: attributes.payload_attributes.timestamp,
+            fee_recipient: attributes.payload_attributes.suggested_fee_recipient,
+            prev_randao: attributes.payload_attributes.prev_randao,
+            gas_limit: attributes.gas_limit,
+            transactions,
+            complete: attributes.no_tx_pool == Some(true),
+        };
+        *self
+            .0
+            .pending_payload
+            .write()
+            .expect("rw-lock.write -> poisoned") = Some(payload);
+        *self
+            .0
+            .pending_block
+            .write()
+            .expect("rw-lock.write -> poisoned") = None;
+    }
+
+    /// The pending block, built once per head and state of the pool.
+    fn pending_block(&self) -> Option<PendingBlock> {
+        let key = (self.0.heads.head()?.hash?, self.0.pool.generation());
+        if let Some((built_for, block)) = self
+            .0
+            .pending_block
+            .read()
+            .expect("rw-lock.read -> poisoned")
+            .as_ref()
+        {
+            if *built_for == key {
+                return Some(block.clone());
+            }
+        }
+
+        let block = self.build_pending_block()?;
+        *self
+            .0
+            .pending_block
+            .write()
+            .expect("rw-lock.write -> poisoned") = Some((key, block.clone()));
+        Some(block)
+    }
+
+    /// The block on top of the head: the payload being built if it is known in full, otherwise
+    /// its forced transactions followed by those the sequencer's pool would have included.
+    fn build_pending_block(&self) -> Option<PendingBlock> {
+        let head = self.0.heads.head()?;
+        let head_hash = head.hash?;
+        let number = as_u128(head.number?) as u64 + 1;
+        let payload = self
+            .0
+            .pending_payload
+            .read()
+            .expect("rw-lock.read -> poisoned")
+            .clone()
+            .filter(|payload| payload.parent_hash == head_hash);
+
+        let parent_fees = BlockFees {
+            number: number - 1,
+            base_fee: head.base_fee_per_gas.map(as_u128).unwrap_or_default(),
+            gas_used: as_u128(head.gas_used),
+            gas_limit: as_u128(head.gas_limit),
+            tips: vec![],
+        };
+        let base_fee = parent_fees.next_base_fee();
+        let gas_limit = payload
+            .as_ref()
+            .and_then(|payload| payload.gas_limit)
+            .unwrap_or(parent_fees.gas_limit as u64);
+
+        let mut transactions = payload
+            .as_ref()
+            .map(|payload| payload.transactions.clone())
+            .unwrap_or_default();
+        if !payload.as_ref().is_some_and(|payload| payload.complete) {
+            let forced_gas = transactions.iter().map(|tx| tx.gas_limit()).sum::<u64>();
+            let space = BlockSpace {
+                gas: gas_limit.saturating_sub(forced_gas),
+                base_fee,
+            };
+            let selected = self.0.miner.select(self.0.pool.content().pending, space);
+            transactions.extend(selected.iter().filter_map(recover));
+        }
+
+        let (timestamp, fee_recipient, prev_randao) = match payload.as_ref() {
+            Some(payload) => (
+                payload.timestamp,
+                payload.fee_recipient,
+                payload.prev_randao,
+            ),
+            None => {
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |now| now.as_secs());
+                let timestamp = now.max(as_u128(head.timestamp) as u64 + 1);
+                (timestamp, Address::ZERO, B256::ZERO)
+            }
+        };
+        // The gas the transactions may use at most: what they have used is only known once sealed.
+        let gas_used = transactions
+            .iter()
+            .map(|tx| tx.gas_limit())
+            .sum::<u64>()
+            .min(gas_limit);
+
+        // Like geth's pending block: no hash, nonce or roots, which are only known once sealed.
+        let block = serde_json::json!({
+            "hash": null,
+            "parentHash": head_hash,
+            "sha3Uncles": EMPTY_OMMER_ROOT_HASH,
+            "miner": fee_recipient,
+            "stateRoot": B256::ZERO,
+            "transactionsRoot": B256::ZERO,
+            "receiptsRoot": B256::ZERO,
+            "logsBloom": Bloom::ZERO,
+            "difficulty": "0x0",
+            "number": format!("{:#x}", number),
+            "gasLimit": format!("{:#x}", gas_limit),
+            "gasUsed": format!("{:#x}", gas_used),
+            "timestamp": format!("{:#x}", timestamp),
+            "extraData": "0x",
+            "mixHash": prev_randao,
+            "nonce": null,
+            "baseFeePerGas": format!("{:#x}", base_fee),
+            "uncles": [],
+            "transactions": [],
+        });
+        Some(PendingBlock {
+            transactions,
+            block,
+        })
+    }
+
+    /// The pending block if `number` asks for it and the sequencer knows it.
+    pub(super) fn local_pending_block(&self, number: BlockNumberOrTag) -> Option<PendingBlock> {
+        if number != BlockNumberOrTag::Pending {
+            return None;
+        }
+        self.pending_block()
+    }
+}
+
+impl PendingBlock {
+    /// The transaction at `index`, as served for the pending block.
+    pub fn transaction(&self, index: usize) -> Option<Transaction> {
+        self.transactions.get(index).cloned().map(from_recovered)
+    }
+
+    /// The block, with its transactions in full or by hash.
+    pub fn to_rich_block(&self, full: bool) -> Option<RichBlock> {
+        let transactions = if full {
+            serde_json::to_value(
+                self.transactions
+                    .iter()
+                    .cloned()
+                    .map(from_recovered)
+                    .collect::<Vec<_>>(),
+            )
+        } else {
+            serde_json::to_value(
+                self.transactions
+                    .iter()
+                    .map(|tx| tx.hash())
+                    .collect::<Vec<_>>(),
+            )
+        };
+        let mut block = self.block.clone();
+        block["transactions"] = transactions.ok()?;
+        serde_json::from_value(block)
+            .map_err(|reason| tracing::warn!("failed to build the pending block: {}", reason))
+            .ok()
+    }
+}
+
+fn recover(raw: &Bytes) -> Option<TransactionSignedEcRecovered> {
+    TransactionSigned::decode_enveloped(&mut raw.as_ref())
+        .ok()?
+        .into_ecrecovered()
+}
@@ -0,0 +1,59 @@
+use alloy_primitives::B256;
+use alloy_rpc_types::{BlockId, BlockNumberOrTag};
+
+use super::Api;
+
+impl Api {
+    /// The block `latest` stands for on a server pinning it: the head `eth_blockNumber` reports,
+    /// and its hash if the head tracker has it.
+    fn pinned_head(&self) -> Option<(u64, Option<B256>)> {
+        if !self.1.pin_latest {
+            return None;
+        }
+        let number: u64 = self
+            .0
+            .current_block_number
+            .read()
+            .expect("rw-lock.read -> poisoned")
+            .saturating_to();
+        if number == 0 {
+            return None;
+        }
+        let hash = self.0.heads.header(number).and_then(|header| header.hash);
+        Some((number, hash))
+    }
+
+    /// `block` as forwarded to the backend: `latest`, or no block at all, is pinned to the
+    /// sequencer's head so that the reads agree with `eth_blockNumber`.
+    pub(super) fn pin_block_id(&self, block: Option<BlockId>) -> Option<BlockId> {
+        match block {
+            None | Some(BlockId::Number(BlockNumberOrTag::Latest)) => {}
+            _ => return block,
+        }
+        let Some((number, hash)) = self.pinned_head() else {
+            return block;
+        };
+        Some(match hash {
+            Some(hash) => BlockId::from(hash),
+            None => BlockId::from(number),
+        })
+    }
+
+    /// The number `block` is pinned to, if it is.
+    pub(super) fn pinned_number(&self, block: Option<BlockId>) -> Option<u64> {
+        match block {
+            None | Some(BlockId::Number(BlockNumberOrTag::Latest)) => {
+                self.pinned_head().map(|(number, _)| number)
+            }
+            _ => None,
+        }
+    }
+
+    /// `number` as forwarded to the backend, see [`Self::pin_block_id`].
+    pub(super) fn pin_block_number(&self, number: BlockNumberOrTag) -> BlockNumberOrTag {
+        match (number, self.pinned_head()) {
+            (BlockNumberOrTag::Latest, Some((head, _))) => BlockNumberOrTag::Number(head),
+            _ => number,
+        }
+    }
+}
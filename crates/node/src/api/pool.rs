@@ -0,0 +1,176 @@
+use std::time::Duration;
+
+use alloy_primitives::Bytes;
+use alloy_rpc_types::BlockId;
+use jsonrpsee::core::ClientError;
+use reth_primitives::TransactionSignedEcRecovered;
+use reth_rpc_api::EthApiClient;
+
+use crate::admission::rules::Verdict;
+use crate::pool::{Pool, PooledTransaction};
+use crate::AnyError;
+
+use super::{recover_raw_transaction, Api};
+
+/// As answered by geth for a transaction already in its pool.
+const ALREADY_KNOWN_MESSAGE: &str = "already known";
+
+impl Api {
+    pub fn pool(&self) -> &Pool {
+        &self.0.pool
+    }
+
+    /// Puts an accepted transaction into the pool and the journal.
+    pub(super) fn accept(&self, transaction: TransactionSignedEcRecovered, raw: Bytes) {
+        if let Some(journal) = self.0.journal.as_ref() {
+            if let Err(reason) = journal.append(&raw) {
+                tracing::error!("failed to journal {}: {}", transaction.hash(), reason);
+            }
+        }
+        let _ = self.0.pending_transactions.send(transaction.clone());
+        self.0.pool.insert(PooledTransaction::new(transaction, raw));
+    }
+
+    /// Re-validates the journaled transactions, re-submits them to the backend
+    /// and puts those that are still pending back into the pool. Only the transactions the
+    /// backend rejects are dropped: if it does not answer, this fails with the journal left as
+    /// it is.
+    pub async fn restore_pool(&self) -> Result<usize, AnyError> {
+        let Some(journal) = self.0.journal.as_ref() else {
+            return Ok(0);
+        };
+
+        let journaled = journal.read()?;
+        let mut restored = 0;
+        for raw in journaled {
+            let transaction = match recover_raw_transaction(&raw) {
+                Ok(transaction) => transaction,
+                Err(reason) => {
+                    tracing::warn!("dropping journaled transaction: {}", reason.message());
+                    continue;
+                }
+            };
+            let hash = transaction.hash();
+
+            if let Err(rejection) = self
+                .0
+                .admission
+                .check(transaction.signer(), transaction.to())
+            {
+                tracing::info!("dropping journaled {}: {}", hash, rejection);
+                continue;
+            }
+            if let Verdict::Deny(rule) = self.0.rules.evaluate(&transaction) {
+                tracing::info!("dropping journaled {}: denied by rule {:?}", hash, rule);
+                continue;
+            }
+
+            let next_nonce = match self.next_nonce(&transaction).await {
+                Ok(next_nonce) => next_nonce,
+                Err(ClientError::Call(reason)) => {
+                    tracing::warn!("dropping journaled {}: {}", hash, reason.message());
+                    continue;
+                }
+                Err(reason) => return Err(unanswered(reason)),
+            };
+            if transaction.nonce() < next_nonce {
+                tracing::debug!("dropping journaled {}: already included", hash);
+                continue;
+            }
+
+            if let Err(reason) = self
+                .backend_eth_api()
+                .send_raw_transaction(raw.clone())
+                .await
+            {
+                match reason {
+                    ClientError::Call(error) if error.message() == ALREADY_KNOWN_MESSAGE => {}
+                    ClientError::Call(error) => {
+                        tracing::info!("dropping journaled {}: {}", hash, error.message());
+                        continue;
+                    }
+                    reason => return Err(unanswered(reason)),
+                }
+            }
+
+            let sender = transaction.signer();
+            self.0.pool.insert(PooledTransaction::new(transaction, raw));
+            self.0.pool.set_nonce(sender, next_nonce);
+            restored += 1;
+        }
+
+        self.compact_pool_journal()?;
+        Ok(restored)
+    }
+
+    /// Removes the transactions that have been included or have expired.
+    pub async fn prune_pool(&self, max_age: Duration) -> Result<(), AnyError> {
+        for sender in self.0.pool.senders() {
+            let next_nonce = self
+                .backend_eth_api()
+                .transaction_count(sender, Some(BlockId::latest()))
+                .await?;
+            let next_nonce = u64::try_from(next_nonce).unwrap_or(u64::MAX);
+            self.0.pool.remove_included(sender, next_nonce);
+        }
+
+        let expired = self.0.pool.remove_expired(max_age);
+        if expired > 0 {
+            tracing::info!("dropped {} expired transactions from the pool", expired);
+        }
+
+        Ok(())
+    }
+
+    /// Removes the transactions included as of `block`, checking the nonces of the senders of
+    /// the pending transactions against it.
+    pub(super) async fn remove_included_at(&self, block: BlockId) -> Result<(), AnyError> {
+        let senders = self.0.pool.content().pending.into_keys();
+        let next_nonces = futures::future::try_join_all(senders.map(|sender| async move {
+            let next_nonce = self
+                .backend_eth_api()
+                .transaction_count(sender, Some(block))
+                .await?;
+            Ok::<_, ClientError>((sender, u64::try_from(next_nonce).unwrap_or(u64::MAX)))
+        }))
+        .await?;
+        for (sender, next_nonce) in next_nonces {
+            self.0.pool.remove_included(sender, next_nonce);
+        }
+        Ok(())
+    }
+
+    /// Rewrites the journal to contain exactly the transactions in the pool.
+    pub fn compact_pool_journal(&self) -> Result<(), AnyError> {
+        let Some(journal) = self.0.journal.as_ref() else {
+            return Ok(());
+        };
+        journal.rewrite(|| {
+            self.0
+                .pool
+                .transactions()
+                .into_iter()
+                .map(|pooled| pooled.raw)
+                .collect()
+        })
+    }
+
+    async fn next_nonce(
+        &self,
+        transaction: &TransactionSignedEcRecovered,
+    ) -> Result<u64, ClientError> {
+        let next_nonce = self
+            .backend_eth_api()
+            .transaction_count(transaction.signer(), Some(BlockId::latest()))
+            .await?;
+        Ok(u64::try_from(next_nonce).unwrap_or(u64::MAX))
+    }
+}
+
+fn unanswered(reason: ClientError) -> AnyError {
+    format!(
+        "failed to restore the pool-journal, the backend did not answer: {}",
+        reason
+    )
+    .into()
+}
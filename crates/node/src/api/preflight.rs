@@ -0,0 +1,107 @@
+use alloy_rpc_types::BlockNumberOrTag;
+use jsonrpsee::core::ClientError;
+use reth_rpc_api::{EngineApiClient, EthApiClient};
+
+use crate::preflight::{Preflight, ENGINE_CAPABILITIES};
+use crate::AnyError;
+
+use super::Api;
+
+impl Api {
+    /// Checks that every node of every backend serves the expected chain, and that the engine
+    /// endpoint accepts the JWT secret and supports the required methods; fails with every
+    /// problem found.
+    ///
+    /// Without an expected chain-id or genesis, the nodes are checked against the primary of
+    /// the `default` backend.
+    pub async fn preflight(&self, preflight: &Preflight) -> Result<(), AnyError> {
+        let mut problems = vec![];
+        let mut expected_chain_id = preflight.chain_id;
+        let mut expected_genesis = preflight.genesis_hash;
+
+        let default = self.0.router.default_backend();
+        let backends = std::iter::once(default).chain(
+            self.0
+                .router
+                .backends()
+                .filter(|backend| backend.name() != default.name()),
+        );
+        for backend in backends {
+            for (url, client) in backend.nodes() {
+                let node = format!("node {} of {:?}", url, backend.name());
+                match client.chain_id().await {
+                    Ok(chain_id) => {
+                        let chain_id = chain_id.map(|chain_id| chain_id.to::<u64>());
+                        tracing::info!("{} chain-id: {:?}", node, chain_id);
+                        match expected_chain_id {
+                            Some(expected) if chain_id != Some(expected) => problems.push(format!(
+                                "{} chain-id is {:?}, expected {}",
+                                node, chain_id, expected
+                            )),
+                            Some(_) => (),
+                            None => expected_chain_id = chain_id,
+                        }
+                    }
+                    Err(reason) => problems.push(format!("{} is unreachable: {}", node, reason)),
+                }
+
+                match client
+                    .block_by_number(BlockNumberOrTag::Earliest, false)
+                    .await
+                {
+                    Ok(genesis) => {
+                        let hash = genesis.and_then(|genesis| genesis.header.hash);
+                        match expected_genesis {
+                            Some(expected) if hash != Some(expected) => problems.push(format!(
+                                "{} genesis is {:?}, expected {}",
+                                node, hash, expected
+                            )),
+                            Some(_) => (),
+                            None => expected_genesis = hash,
+                        }
+                    }
+                    Err(reason) => {
+                        problems.push(format!("{} failed to tell the genesis: {}", node, reason))
+                    }
+                }
+            }
+        }
+
+        let capabilities = ENGINE_CAPABILITIES.iter().map(|m| m.to_string()).collect();
+        match self
+            .backend_engine_api()
+            .exchange_capabilities(capabilities)
+            .await
+        {
+            Ok(supported) => {
+                let missing = preflight
+                    .engine_methods
+                    .iter()
+                    .filter(|method| !supported.contains(method))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                if !missing.is_empty() {
+                    problems.push(format!("engine endpoint lacks {}", missing.join(", ")));
+                }
+            }
+            // The request got through: the JWT secret is accepted, but the methods are unknown.
+            Err(ClientError::Call(reason)) => problems.push(format!(
+                "engine endpoint does not tell its capabilities: {}",
+                reason
+            )),
+            Err(reason) => problems.push(format!(
+                "engine endpoint rejected the request (is the JWT secret right?): {}",
+                reason
+            )),
+        }
+
+        if problems.is_empty() {
+            tracing::info!("preflight checks passed");
+            return Ok(());
+        }
+        for problem in problems.iter() {
+            tracing::error!("preflight: {}", problem);
+        }
+        Err(format!("preflight checks failed: {}", problems.join("; ")).into())
+    }
+}
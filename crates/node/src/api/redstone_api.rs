@@ -0,0 +1,178 @@
+use alloy_primitives::{address, keccak256, Address, Bytes, U256};
+use alloy_rpc_types::state::StateOverride;
+use alloy_rpc_types::{BlockId, TransactionRequest};
+use jsonrpsee::core::{ClientError, RpcResult};
+use jsonrpsee::types::error::INTERNAL_ERROR_CODE;
+use jsonrpsee::types::{ErrorObject, ErrorObjectOwned};
+use reth_rpc_api::EthApiClient;
+
+use crate::l1_fee::{GasEstimate, L1FeeEstimate, L1FeeParams};
+use crate::AnyError;
+
+use super::{recover_raw_transaction, Api, RedstoneApiServer};
+
+const L1_BLOCK_ADDRESS: Address = address!("4200000000000000000000000000000000000015");
+const GAS_PRICE_ORACLE_ADDRESS: Address = address!("420000000000000000000000000000000000000F");
+
+/// As answered by geth for a call that reverted, along with the revert data.
+const EXECUTION_REVERTED_CODE: i32 = 3;
+const EXECUTION_REVERTED_MESSAGE: &str = "execution reverted";
+
+/// The OP Stack deposit transaction type; deposits pay no L1 data fee.
+const DEPOSIT_TX_TYPE: u8 = 0x7e;
+
+/// A generous size of the unsigned encoding of a transaction without its calldata and access-list:
+/// the envelope, chain-id, nonce, fees, gas-limit, recipient and value.
+const UNSIGNED_TX_OVERHEAD: u64 = 72;
+
+impl Api {
+    /// The L1 data fee parameters as of the current block; fetched once per block.
+    async fn l1_fee_params(&self) -> Result<L1FeeParams, AnyError> {
+        let block_number = *self
+            .0
+            .current_block_number
+            .read()
+            .expect("rw-lock.read -> poisoned");
+        if let Some((cached_at, params)) = self
+            .0
+            .l1_fee_params
+            .read()
+            .expect("rw-lock.read -> poisoned")
+            .as_ref()
+        {
+            if *cached_at == block_number {
+                return Ok(params.clone());
+            }
+        }
+
+        let getters = futures::try_join!(
+            self.call_getter(L1_BLOCK_ADDRESS, "basefee()"),
+            self.call_getter(L1_BLOCK_ADDRESS, "blobBaseFee()"),
+            self.call_getter(L1_BLOCK_ADDRESS, "baseFeeScalar()"),
+            self.call_getter(L1_BLOCK_ADDRESS, "blobBaseFeeScalar()"),
+            async {
+                match self
+                    .call_getter(GAS_PRICE_ORACLE_ADDRESS, "isFjord()")
+                    .await
+                {
+                    Ok(is_fjord) => Ok(is_fjord == U256::from(1)),
+                    // `isFjord()` is absent before Fjord: calling it reverts.
+                    Err(reason) if is_revert(&reason) => Ok(false),
+                    Err(reason) => Err(reason),
+                }
+            },
+        )?;
+        let (l1_base_fee, blob_base_fee, base_fee_scalar, blob_base_fee_scalar, fjord) = getters;
+        let params = L1FeeParams {
+            l1_base_fee,
+            blob_base_fee,
+            base_fee_scalar: base_fee_scalar.saturating_to(),
+            blob_base_fee_scalar: blob_base_fee_scalar.saturating_to(),
+            fjord,
+        };
+
+        *self
+            .0
+            .l1_fee_params
+            .write()
+            .expect("rw-lock.write -> poisoned") = Some((block_number, params.clone()));
+        Ok(params)
+    }
+
+    /// Calls the parameterless view-function `signature` of `contract` returning a single word.
+    async fn call_getter(&self, contract: Address, signature: &str) -> Result<U256, AnyError> {
+        let selector = Bytes::copy_from_slice(&keccak256(signature)[..4]);
+        let request = TransactionRequest {
+            to: Some(contract),
+            input: selector.into(),
+            ..Default::default()
+        };
+        let output = self
+            .backend_eth_api()
+            .call(request, None, None, None)
+            .await?;
+        if output.len() != 32 {
+            return Err(format!(
+                "unexpected output of {}.{}: {}",
+                contract, signature, output
+            )
+            .into());
+        }
+        Ok(U256::from_be_slice(&output))
+    }
+}
+
+#[async_trait::async_trait]
+impl RedstoneApiServer for Api {
+    async fn estimate_l1_fee(&self, transaction: Bytes) -> RpcResult<L1FeeEstimate> {
+        let recovered = recover_raw_transaction(&transaction)?;
+        let params = self.l1_fee_params().await.map_err(params_error)?;
+
+        let mut estimate = params.estimate(&transaction);
+        if u8::from(recovered.tx_type()) == DEPOSIT_TX_TYPE {
+            estimate.l1_fee = U256::ZERO;
+        }
+        Ok(estimate)
+    }
+
+    async fn estimate_gas(
+        &self,
+        request: TransactionRequest,
+        block_number: Option<BlockId>,
+        state_override: Option<StateOverride>,
+    ) -> RpcResult<GasEstimate> {
+        let unsigned_size = unsigned_size(&request);
+        let pinned = self.pin_block_id(block_number);
+        let (gas, params) = futures::try_join!(
+            async {
+                self.route("eth_estimateGas", block_number)
+                    .call(|client| {
+                        client.estimate_gas(request.clone(), pinned, state_override.clone())
+                    })
+                    .await
+            },
+            async { self.l1_fee_params().await.map_err(params_error) },
+        )?;
+
+        Ok(GasEstimate {
+            gas,
+            l1_fee: params.estimate_upper_bound(unsigned_size),
+        })
+    }
+}
+
+fn params_error(reason: AnyError) -> ErrorObjectOwned {
+    ErrorObject::owned(
+        INTERNAL_ERROR_CODE,
+        format!("failed to read the L1 fee parameters: {}", reason),
+        None::<()>,
+    )
+}
+
+/// An upper bound of the size of the unsigned encoding of the transaction `request` describes.
+fn unsigned_size(request: &TransactionRequest) -> u64 {
+    let calldata_size = request
+        .input
+        .input()
+        .map_or(0, |input| input.len() as u64 + 3);
+    let access_list_size = request.access_list.as_ref().map_or(0, |access_list| {
+        access_list
+            .0
+            .iter()
+            .map(|item| 3 + 21 + 3 + 33 * item.storage_keys.len() as u64)
+            .sum::<u64>()
+            + 3
+    });
+    UNSIGNED_TX_OVERHEAD + calldata_size + access_list_size
+}
+
+/// Whether `reason` is the execution of a call having reverted, rather than a failure to call.
+fn is_revert(reason: &AnyError) -> bool {
+    match reason.downcast_ref::<ClientError>() {
+        Some(ClientError::Call(error)) => {
+            error.code() == EXECUTION_REVERTED_CODE
+                || error.message().starts_with(EXECUTION_REVERTED_MESSAGE)
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,108 @@
+use std::future::Future;
+
+use alloy_rpc_types::BlockNumberOrTag;
+use jsonrpsee::core::RpcResult;
+use reth_rpc_api::EthApiClient;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::response_cache::{Lifetime, ResponseCache};
+use crate::AnyError;
+
+use super::{as_u128, Api};
+
+impl Api {
+    pub fn response_cache(&self) -> &ResponseCache {
+        &self.0.response_cache
+    }
+
+    /// Reads the finalized head from the backend, so that the results referring to
+    /// the blocks below it are kept for good.
+    pub async fn refresh_finalized(&self) -> Result<(), AnyError> {
+        let finalized = self
+            .backend_eth_api()
+            .block_by_number(BlockNumberOrTag::Finalized, false)
+            .await?
+            .and_then(|block| block.header.number);
+        if let Some(finalized) = finalized {
+            self.0
+                .response_cache
+                .set_finalized(as_u128(finalized) as u64);
+        }
+        Ok(())
+    }
+
+    /// Serves `method` from the response cache or by a coalesced `fetch`;
+    /// caches the fetched result for `lifetime`, if any.
+    pub(super) async fn cached<T, F>(
+        &self,
+        method: &str,
+        params: impl Serialize,
+        lifetime: Option<Lifetime>,
+        fetch: F,
+    ) -> RpcResult<T>
+    where
+        T: Serialize + DeserializeOwned,
+        F: Future<Output = RpcResult<T>>,
+    {
+        self.cached_as(method, params, lifetime, false, fetch).await
+    }
+
+    /// As [`Self::cached`], but an empty result is cached too, see
+    /// [`ResponseCache::insert_even_if_empty`].
+    pub(super) async fn cached_even_if_empty<T, F>(
+        &self,
+        method: &str,
+        params: impl Serialize,
+        lifetime: Option<Lifetime>,
+        fetch: F,
+    ) -> RpcResult<T>
+    where
+        T: Serialize + DeserializeOwned,
+        F: Future<Output = RpcResult<T>>,
+    {
+        self.cached_as(method, params, lifetime, true, fetch).await
+    }
+
+    async fn cached_as<T, F>(
+        &self,
+        method: &str,
+        params: impl Serialize,
+        lifetime: Option<Lifetime>,
+        keep_empty: bool,
+        fetch: F,
+    ) -> RpcResult<T>
+    where
+        T: Serialize + DeserializeOwned,
+        F: Future<Output = RpcResult<T>>,
+    {
+        let Some(lifetime) = lifetime else {
+            return self.coalesced(method, params, fetch).await;
+        };
+        if let Some(cached) = self.0.response_cache.get(method, &params) {
+            return Ok(cached);
+        }
+        let result = self.0.coalescer.run(method, &params, fetch).await?;
+        let cache = &self.0.response_cache;
+        if keep_empty {
+            cache.insert_even_if_empty(method, &params, &result, lifetime);
+        } else {
+            cache.insert(method, &params, &result, lifetime);
+        }
+        Ok(result)
+    }
+
+    /// Serves `method` by `fetch`, sharing the result with the identical requests in flight.
+    pub(super) async fn coalesced<T, F>(
+        &self,
+        method: &str,
+        params: impl Serialize,
+        fetch: F,
+    ) -> RpcResult<T>
+    where
+        T: Serialize + DeserializeOwned,
+        F: Future<Output = RpcResult<T>>,
+    {
+        self.0.coalescer.run(method, &params, fetch).await
+    }
+}
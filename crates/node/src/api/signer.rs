@@ -0,0 +1,104 @@
+use alloy_primitives::Address;
+use alloy_rpc_types::{BlockId, TransactionRequest};
+use jsonrpsee::core::RpcResult;
+use jsonrpsee::types::error::{INTERNAL_ERROR_CODE, INVALID_PARAMS_CODE};
+use jsonrpsee::types::{ErrorObject, ErrorObjectOwned};
+use reth_primitives::{Transaction, TransactionKind, TxEip1559};
+use reth_rpc_api::EthApiServer;
+
+use crate::signer::{Signer, SignerError};
+
+use super::{as_u128, Api};
+
+impl Api {
+    /// The local signer, if this server signs on behalf of its accounts.
+    pub(super) fn signer(&self) -> Option<&Signer> {
+        self.1.signer.as_deref()
+    }
+
+    /// Completes `request` into an EIP-1559 transaction from one of `signer`'s accounts:
+    /// the nonce, the gas and the fees missing are filled in as the sequencer would suggest them.
+    pub(super) async fn fill_transaction(
+        &self,
+        signer: &Signer,
+        request: TransactionRequest,
+    ) -> RpcResult<(Address, Transaction)> {
+        let from = request
+            .from
+            .ok_or_else(|| invalid_params("missing `from`".to_owned()))?;
+        if !signer.has_account(&from) {
+            return Err(signer_error(SignerError::UnknownAccount(from)));
+        }
+
+        let chain_id = match request.chain_id {
+            Some(chain_id) => as_u128(chain_id) as u64,
+            None => self
+                .chain_id()
+                .await?
+                .map(|chain_id| chain_id.to::<u64>())
+                .ok_or_else(|| invalid_params("unknown chain-id".to_owned()))?,
+        };
+        let nonce = match request.nonce {
+            Some(nonce) => as_u128(nonce) as u64,
+            None => self
+                .transaction_count(from, Some(BlockId::pending()))
+                .await?
+                .saturating_to(),
+        };
+        let gas_limit = match request.gas {
+            Some(gas) => as_u128(gas) as u64,
+            None => self
+                .estimate_gas(request.clone(), Some(BlockId::pending()), None)
+                .await?
+                .saturating_to(),
+        };
+        let max_priority_fee_per_gas = match request.max_priority_fee_per_gas {
+            Some(tip) => as_u128(tip),
+            None => self.max_priority_fee_per_gas().await?.saturating_to(),
+        };
+        let max_fee_per_gas = match request.max_fee_per_gas.or(request.gas_price) {
+            Some(max_fee) => as_u128(max_fee),
+            None => {
+                let gas_price: u128 = self.gas_price().await?.saturating_to();
+                let base_fee = gas_price.saturating_sub(max_priority_fee_per_gas);
+                base_fee
+                    .saturating_mul(2)
+                    .saturating_add(max_priority_fee_per_gas)
+            }
+        };
+        let access_list = match request.access_list.as_ref() {
+            Some(access_list) => serde_json::to_value(access_list)
+                .and_then(serde_json::from_value)
+                .map_err(|reason| invalid_params(format!("invalid access-list: {}", reason)))?,
+            None => Default::default(),
+        };
+
+        let transaction = Transaction::Eip1559(TxEip1559 {
+            chain_id,
+            nonce,
+            gas_limit,
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+            to: match request.to {
+                Some(to) => TransactionKind::Call(to),
+                None => TransactionKind::Create,
+            },
+            value: request.value.unwrap_or_default(),
+            access_list,
+            input: request.input.input().cloned().unwrap_or_default(),
+        });
+        Ok((from, transaction))
+    }
+}
+
+pub(super) fn signer_error(error: SignerError) -> ErrorObjectOwned {
+    let code = match error {
+        SignerError::UnknownAccount(_) | SignerError::InvalidTypedData(_) => INVALID_PARAMS_CODE,
+        SignerError::Signing(_) => INTERNAL_ERROR_CODE,
+    };
+    ErrorObject::owned(code, error.to_string(), None::<()>)
+}
+
+fn invalid_params(message: String) -> ErrorObjectOwned {
+    ErrorObject::owned(INVALID_PARAMS_CODE, message, None::<()>)
+}
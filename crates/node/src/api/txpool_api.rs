@@ -0,0 +1,83 @@
+use std::collections::BTreeMap;
+
+use alloy_primitives::Address;
+use jsonrpsee::core::RpcResult;
+use reth_rpc_api::TxPoolApiServer;
+use reth_rpc_types::txpool::{
+    TxpoolContent, TxpoolContentFrom, TxpoolInspect, TxpoolInspectSummary, TxpoolStatus,
+};
+use reth_rpc_types::Transaction;
+use reth_rpc_types_compat::transaction::from_recovered;
+
+use crate::pool::PooledTransaction;
+
+use super::Api;
+
+#[async_trait::async_trait]
+impl TxPoolApiServer for Api {
+    async fn txpool_status(&self) -> RpcResult<TxpoolStatus> {
+        let content = self.0.pool.content();
+        Ok(TxpoolStatus {
+            pending: content.pending.values().map(Vec::len).sum::<usize>() as u64,
+            queued: content.queued.values().map(Vec::len).sum::<usize>() as u64,
+        })
+    }
+
+    async fn txpool_inspect(&self) -> RpcResult<TxpoolInspect> {
+        let content = self.0.pool.content();
+        Ok(TxpoolInspect {
+            pending: by_sender(content.pending, summary),
+            queued: by_sender(content.queued, summary),
+        })
+    }
+
+    async fn txpool_content_from(&self, from: Address) -> RpcResult<TxpoolContentFrom> {
+        let (pending, queued) = self.0.pool.content_from(from);
+        Ok(TxpoolContentFrom {
+            pending: by_nonce(pending, transaction),
+            queued: by_nonce(queued, transaction),
+        })
+    }
+
+    async fn txpool_content(&self) -> RpcResult<TxpoolContent> {
+        let content = self.0.pool.content();
+        Ok(TxpoolContent {
+            pending: by_sender(content.pending, transaction),
+            queued: by_sender(content.queued, transaction),
+        })
+    }
+}
+
+fn by_sender<T>(
+    transactions: BTreeMap<Address, Vec<PooledTransaction>>,
+    f: impl Fn(PooledTransaction) -> T + Copy,
+) -> BTreeMap<Address, BTreeMap<String, T>> {
+    transactions
+        .into_iter()
+        .map(|(sender, transactions)| (sender, by_nonce(transactions, f)))
+        .collect()
+}
+
+fn by_nonce<T>(
+    transactions: Vec<PooledTransaction>,
+    f: impl Fn(PooledTransaction) -> T,
+) -> BTreeMap<String, T> {
+    transactions
+        .into_iter()
+        .map(|pooled| (pooled.nonce().to_string(), f(pooled)))
+        .collect()
+}
+
+fn transaction(pooled: PooledTransaction) -> Transaction {
+    from_recovered(pooled.transaction)
+}
+
+fn summary(pooled: PooledTransaction) -> TxpoolInspectSummary {
+    let transaction = &pooled.transaction;
+    TxpoolInspectSummary {
+        to: transaction.to(),
+        value: transaction.value().into(),
+        gas: transaction.gas_limit().into(),
+        gas_price: transaction.max_fee_per_gas().into(),
+    }
+}
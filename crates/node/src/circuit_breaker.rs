@@ -0,0 +1,128 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// When a backend is deemed unhealthy, and for how long it is not sent requests.
+#[derive(Debug, Clone, Copy)]
+pub struct BreakerConfig {
+    /// The failures in a row that open the circuit; zero disables the breaker.
+    pub failure_threshold: u32,
+    /// How long the circuit stays open before a request is let through to probe the backend.
+    pub cooldown: Duration,
+}
+
+impl Default for BreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 0,
+            cooldown: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Fails the requests to a backend fast while it is unhealthy.
+///
+/// After `failure_threshold` failures in a row the circuit opens: requests are failed without
+/// being sent for `cooldown`. Then a single request is let through; the circuit closes if it
+/// succeeds, and opens again otherwise.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    backend: String,
+    /// The requests guarded: `reads` or `writes`.
+    path: &'static str,
+    config: BreakerConfig,
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum State {
+    Closed {
+        failures: u32,
+    },
+    Open {
+        until: Instant,
+    },
+    /// A probe has been let through at `since`.
+    HalfOpen {
+        since: Instant,
+    },
+}
+
+impl CircuitBreaker {
+    pub fn new(backend: &str, path: &'static str, config: BreakerConfig) -> Self {
+        Self {
+            backend: backend.to_owned(),
+            path,
+            config,
+            state: Mutex::new(State::Closed { failures: 0 }),
+        }
+    }
+
+    /// Whether a request may be sent now; if not, the time after which the backend is probed.
+    pub fn admit(&self) -> Result<(), Duration> {
+        if self.config.failure_threshold == 0 {
+            return Ok(());
+        }
+        let now = Instant::now();
+        let mut state = self.state.lock().expect("mutex.lock -> poisoned");
+        match *state {
+            State::Closed { .. } => Ok(()),
+            State::Open { until } if now < until => Err(until - now),
+            // A probe that never completed (e.g. dropped with its client) does not hold the
+            // circuit half-open for longer than a cooldown.
+            State::HalfOpen { since } if now < since + self.config.cooldown => {
+                Err(since + self.config.cooldown - now)
+            }
+            State::Open { .. } | State::HalfOpen { .. } => {
+                tracing::info!("probing backend {:?} for {}", self.backend, self.path);
+                *state = State::HalfOpen { since: now };
+                Ok(())
+            }
+        }
+    }
+
+    /// Records the outcome of a request that has been admitted.
+    pub fn record(&self, healthy: bool) {
+        if self.config.failure_threshold == 0 {
+            return;
+        }
+        let mut state = self.state.lock().expect("mutex.lock -> poisoned");
+        let next = match (*state, healthy) {
+            (State::Closed { .. }, true) => State::Closed { failures: 0 },
+            (State::Open { .. } | State::HalfOpen { .. }, true) => {
+                tracing::info!("backend {:?} recovered for {}", self.backend, self.path);
+                State::Closed { failures: 0 }
+            }
+            (State::Closed { failures }, false) if failures + 1 < self.config.failure_threshold => {
+                State::Closed {
+                    failures: failures + 1,
+                }
+            }
+            (State::Open { until }, false) => State::Open { until },
+            (State::Closed { .. } | State::HalfOpen { .. }, false) => {
+                tracing::warn!(
+                    "backend {:?} is unhealthy: failing its {} for {:?}",
+                    self.backend,
+                    self.path,
+                    self.config.cooldown
+                );
+                metrics::counter!(
+                    "sequencer_circuit_breaker_trips_total",
+                    "backend" => self.backend.clone(),
+                    "path" => self.path
+                )
+                .increment(1);
+                State::Open {
+                    until: Instant::now() + self.config.cooldown,
+                }
+            }
+        };
+        *state = next;
+        let open = !matches!(next, State::Closed { .. });
+        metrics::gauge!(
+            "sequencer_circuit_breaker_open",
+            "backend" => self.backend.clone(),
+            "path" => self.path
+        )
+        .set(open as u8 as f64);
+    }
+}
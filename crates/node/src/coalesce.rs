@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+
+use jsonrpsee::core::RpcResult;
+use jsonrpsee::types::ErrorObjectOwned;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use tokio::sync::watch;
+
+/// The result of a request, once it is known.
+type Outcome = Option<Result<Value, ErrorObjectOwned>>;
+
+/// Lets the concurrent identical requests share a single call to the backend.
+#[derive(Debug, Default)]
+pub struct Coalescer {
+    in_flight: Mutex<HashMap<String, watch::Receiver<Outcome>>>,
+}
+
+/// Withdraws a request from the in-flight ones when it completes or is dropped.
+struct Leading<'a> {
+    coalescer: &'a Coalescer,
+    key: String,
+}
+
+impl Coalescer {
+    /// Runs `fetch` for `method` with `params`, unless an identical request is in flight:
+    /// then shares its result.
+    pub async fn run<T, F>(&self, method: &str, params: &impl Serialize, fetch: F) -> RpcResult<T>
+    where
+        T: Serialize + DeserializeOwned,
+        F: Future<Output = RpcResult<T>>,
+    {
+        let Ok(params) = serde_json::to_string(params) else {
+            return fetch.await;
+        };
+        let key = format!("{}:{}", method, params);
+
+        let leader = {
+            let mut in_flight = self.in_flight.lock().expect("mutex.lock -> poisoned");
+            match in_flight.get(&key) {
+                Some(receiver) => Err(receiver.clone()),
+                None => {
+                    let (sender, receiver) = watch::channel(None);
+                    in_flight.insert(key.clone(), receiver);
+                    Ok(sender)
+                }
+            }
+        };
+
+        let sender = match leader {
+            Ok(sender) => sender,
+            Err(mut receiver) => {
+                let outcome = receiver
+                    .wait_for(Option::is_some)
+                    .await
+                    .ok()
+                    .and_then(|outcome| outcome.clone());
+                match outcome {
+                    Some(Ok(value)) => {
+                        if let Ok(result) = serde_json::from_value(value) {
+                            metrics::counter!("sequencer_request_coalescing_total", "result" => "hit")
+                                .increment(1);
+                            return Ok(result);
+                        }
+                    }
+                    Some(Err(error)) => {
+                        metrics::counter!("sequencer_request_coalescing_total", "result" => "hit")
+                            .increment(1);
+                        return Err(error);
+                    }
+                    // The leading request has been dropped.
+                    None => (),
+                }
+                metrics::counter!("sequencer_request_coalescing_total", "result" => "miss")
+                    .increment(1);
+                return fetch.await;
+            }
+        };
+
+        metrics::counter!("sequencer_request_coalescing_total", "result" => "miss").increment(1);
+        let leading = Leading {
+            coalescer: self,
+            key,
+        };
+        let result = fetch.await;
+        drop(leading);
+
+        if sender.receiver_count() > 0 {
+            let outcome = match &result {
+                Ok(result) => serde_json::to_value(result).ok().map(Ok),
+                Err(error) => Some(Err(error.clone())),
+            };
+            if outcome.is_some() {
+                sender.send_replace(outcome);
+            }
+        }
+        result
+    }
+}
+
+impl Drop for Leading<'_> {
+    fn drop(&mut self) {
+        self.coalescer
+            .in_flight
+            .lock()
+            .expect("mutex.lock -> poisoned")
+            .remove(&self.key);
+    }
+}
@@ -0,0 +1,228 @@
+use std::time::Duration;
+
+use jsonrpsee::core::ClientError;
+use jsonrpsee::http_client::transport;
+use jsonrpsee::types::{ErrorObject, ErrorObjectOwned};
+
+use crate::admission::Rejection;
+
+/// The backend could not be reached, or is not being sent requests for now.
+pub const BACKEND_UNAVAILABLE_CODE: i32 = -32010;
+/// The backend did not answer in time.
+pub const BACKEND_TIMEOUT_CODE: i32 = -32011;
+/// The backend answered with something that is not a JSON-RPC response.
+pub const BACKEND_MALFORMED_RESPONSE_CODE: i32 = -32012;
+/// The backend refused the request for its size.
+pub const BACKEND_REQUEST_TOO_LARGE_CODE: i32 = -32013;
+/// As answered by geth for an unknown filter-id.
+pub const FILTER_NOT_FOUND_CODE: i32 = -32000;
+/// The request lacks a valid API-key. EIP-1474 has no such code, and takes -32000 to -32006:
+/// like the codes above, this one is past them in the range reserved for server errors.
+pub const UNAUTHORIZED_CODE: i32 = -32014;
+/// EIP-1474 "Transaction rejected".
+pub const POLICY_REJECTION_CODE: i32 = -32003;
+/// EIP-1474 "Limit exceeded".
+pub const LIMIT_EXCEEDED_CODE: i32 = -32005;
+
+/// The errors the sequencer answers with on its own account, as opposed to the errors of the
+/// backends, which are relayed as they are.
+///
+/// Each kind has a code of its own, and its `data` tells the `kind` apart along with whatever
+/// a client needs to act on it: the `backend` at fault, when to retry (`retryAfterMs`), or the
+/// `limit` exceeded.
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum ProxyError {
+    #[error("backend {backend:?} is unavailable: {reason}")]
+    BackendUnavailable {
+        backend: String,
+        reason: String,
+        retry_after: Option<Duration>,
+    },
+    #[error("backend {backend:?} timed out")]
+    BackendTimeout { backend: String },
+    #[error("backend {backend:?} sent a malformed response: {reason}")]
+    BackendMalformedResponse { backend: String, reason: String },
+    #[error("backend {backend:?} refused the request as too large")]
+    BackendRequestTooLarge { backend: String },
+    #[error("{reason}")]
+    Unauthorized { reason: String },
+    #[error("limit exceeded")]
+    RateLimited {
+        /// The budget exhausted: a class of methods, or a quota window.
+        limit: RateLimit,
+        retry_after: Duration,
+    },
+    #[error("{0}")]
+    PolicyRejection(Rejection),
+    #[error("query returned more than {max_results} results")]
+    LogsResultsExceeded { max_results: usize },
+    #[error("block range of {block_range} blocks exceeds the limit of {max_block_range}")]
+    LogsBlockRangeExceeded {
+        block_range: u64,
+        max_block_range: u64,
+    },
+    #[error("filter not found")]
+    FilterNotFound,
+    #[error("too many filters installed, the limit is {max_filters}")]
+    FiltersExceeded { max_filters: usize },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum RateLimit {
+    Class(&'static str),
+    Quota(&'static str),
+}
+
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ErrorData<'a> {
+    kind: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    backend: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    retry_after_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    class: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    quota: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    limit: Option<u64>,
+}
+
+impl ProxyError {
+    /// Tells apart why a request to `backend` failed; the errors the backend answered with are
+    /// relayed as they are.
+    pub fn from_client_error(backend: &str, error: ClientError) -> ErrorObjectOwned {
+        let backend = backend.to_owned();
+        let error = match error {
+            ClientError::Call(error_object) => return error_object,
+            ClientError::RequestTimeout => Self::BackendTimeout { backend },
+            ClientError::ParseError(reason) => Self::BackendMalformedResponse {
+                backend,
+                reason: reason.to_string(),
+            },
+            ClientError::InvalidRequestId(reason) => Self::BackendMalformedResponse {
+                backend,
+                reason: reason.to_string(),
+            },
+            error if is_request_too_large(&error) => Self::BackendRequestTooLarge { backend },
+            error => Self::BackendUnavailable {
+                backend,
+                reason: error.to_string(),
+                retry_after: None,
+            },
+        };
+        error.into()
+    }
+
+    pub fn code(&self) -> i32 {
+        match self {
+            Self::BackendUnavailable { .. } => BACKEND_UNAVAILABLE_CODE,
+            Self::BackendTimeout { .. } => BACKEND_TIMEOUT_CODE,
+            Self::BackendMalformedResponse { .. } => BACKEND_MALFORMED_RESPONSE_CODE,
+            Self::BackendRequestTooLarge { .. } => BACKEND_REQUEST_TOO_LARGE_CODE,
+            Self::Unauthorized { .. } => UNAUTHORIZED_CODE,
+            Self::RateLimited { .. } => LIMIT_EXCEEDED_CODE,
+            Self::PolicyRejection(_) => POLICY_REJECTION_CODE,
+            Self::LogsResultsExceeded { .. }
+            | Self::LogsBlockRangeExceeded { .. }
+            | Self::FiltersExceeded { .. } => LIMIT_EXCEEDED_CODE,
+            Self::FilterNotFound => FILTER_NOT_FOUND_CODE,
+        }
+    }
+
+    /// The name of the kind, as found in the `data` and in the metrics.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::BackendUnavailable { .. } => "backend_unavailable",
+            Self::BackendTimeout { .. } => "backend_timeout",
+            Self::BackendMalformedResponse { .. } => "backend_malformed_response",
+            Self::BackendRequestTooLarge { .. } => "backend_request_too_large",
+            Self::Unauthorized { .. } => "unauthorized",
+            Self::RateLimited { .. } => "rate_limited",
+            Self::PolicyRejection(_) => "policy_rejection",
+            Self::LogsResultsExceeded { .. } => "logs_results_exceeded",
+            Self::LogsBlockRangeExceeded { .. } => "logs_block_range_exceeded",
+            Self::FilterNotFound => "filter_not_found",
+            Self::FiltersExceeded { .. } => "filters_exceeded",
+        }
+    }
+
+    fn data(&self) -> ErrorData<'_> {
+        let mut data = ErrorData {
+            kind: self.kind(),
+            backend: None,
+            retry_after_ms: None,
+            class: None,
+            quota: None,
+            reason: None,
+            limit: None,
+        };
+        match self {
+            Self::BackendUnavailable {
+                backend,
+                retry_after,
+                ..
+            } => {
+                data.backend = Some(backend);
+                data.retry_after_ms = retry_after.map(|retry_after| as_millis(&retry_after));
+            }
+            Self::BackendTimeout { backend }
+            | Self::BackendMalformedResponse { backend, .. }
+            | Self::BackendRequestTooLarge { backend } => {
+                data.backend = Some(backend);
+            }
+            Self::Unauthorized { .. } | Self::FilterNotFound => {}
+            Self::RateLimited { limit, retry_after } => {
+                data.retry_after_ms = Some(as_millis(retry_after));
+                match limit {
+                    RateLimit::Class(class) => data.class = Some(*class),
+                    RateLimit::Quota(window) => data.quota = Some(*window),
+                }
+            }
+            Self::PolicyRejection(rejection) => data.reason = Some(rejection.reason()),
+            Self::LogsResultsExceeded { max_results } => data.limit = Some(*max_results as u64),
+            Self::LogsBlockRangeExceeded {
+                max_block_range, ..
+            } => data.limit = Some(*max_block_range),
+            Self::FiltersExceeded { max_filters } => data.limit = Some(*max_filters as u64),
+        }
+        data
+    }
+}
+
+impl From<ProxyError> for ErrorObjectOwned {
+    fn from(error: ProxyError) -> Self {
+        metrics::counter!("sequencer_proxy_errors_total", "kind" => error.kind()).increment(1);
+        ErrorObject::owned(error.code(), error.to_string(), Some(error.data()))
+    }
+}
+
+/// Whether `error` is for a backend not answering, so that it is not known whether the request
+/// took effect, as opposed to its rejecting the request.
+pub fn is_unanswered(error: &ErrorObjectOwned) -> bool {
+    matches!(
+        error.code(),
+        BACKEND_UNAVAILABLE_CODE | BACKEND_TIMEOUT_CODE | BACKEND_MALFORMED_RESPONSE_CODE
+    )
+}
+
+/// Whether the request was refused for its size, by the backend (HTTP 413) or before being
+/// sent.
+pub fn is_request_too_large(error: &ClientError) -> bool {
+    let ClientError::Transport(error) = error else {
+        return false;
+    };
+    matches!(
+        error.downcast_ref::<transport::Error>(),
+        Some(transport::Error::RequestTooLarge)
+            | Some(transport::Error::Rejected { status_code: 413 })
+    )
+}
+
+/// Rounded up, so that retrying after this many milliseconds is never too early.
+fn as_millis(duration: &Duration) -> u64 {
+    duration.as_nanos().div_ceil(1_000_000) as u64
+}
@@ -0,0 +1,217 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use alloy_primitives::B128;
+use alloy_rpc_types::Filter;
+use reth_primitives::TransactionSignedEcRecovered;
+use reth_rpc_types::FilterId;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::TryRecvError;
+
+use crate::rate_limit::ClientId;
+
+/// The filters installed via `eth_newFilter` and friends, kept by the sequencer itself
+/// so that their ids do not depend on the backend serving them.
+#[derive(Debug)]
+pub struct Filters {
+    ttl: Duration,
+    max_filters: usize,
+    max_filters_per_client: usize,
+    installed: Mutex<Installations>,
+}
+
+#[derive(Debug, Default)]
+struct Installations {
+    filters: HashMap<FilterId, Installed>,
+    /// The number of filters installed by each client of server [B].
+    per_client: HashMap<ClientId, usize>,
+}
+
+#[derive(Debug)]
+pub enum FilterKind {
+    Logs(Filter),
+    Blocks,
+    /// Receives the transactions accepted by the sequencer.
+    PendingTransactions {
+        full: bool,
+        accepted: broadcast::Receiver<TransactionSignedEcRecovered>,
+    },
+}
+
+/// What a filter has to report on, as of its last poll.
+#[derive(Debug)]
+pub enum Changes {
+    /// The logs matching the filter in the blocks `from..=to`; `to` is `None` without new blocks.
+    Logs {
+        filter: Filter,
+        from: u64,
+        to: Option<u64>,
+    },
+    /// The blocks `from..=to`; `to` is `None` without new blocks.
+    Blocks { from: u64, to: Option<u64> },
+    PendingTransactions {
+        full: bool,
+        transactions: Vec<TransactionSignedEcRecovered>,
+    },
+}
+
+#[derive(Debug)]
+struct Installed {
+    kind: FilterKind,
+    /// The first block not reported yet.
+    cursor: u64,
+    last_polled: Instant,
+    owner: Option<ClientId>,
+}
+
+impl Filters {
+    /// Filters not polled for `ttl` are uninstalled by [`Filters::evict_idle`]. At most
+    /// `max_filters` are installed at once, `max_filters_per_client` of them by the same client.
+    pub fn new(ttl: Duration, max_filters: usize, max_filters_per_client: usize) -> Self {
+        Self {
+            ttl,
+            max_filters,
+            max_filters_per_client,
+            installed: Default::default(),
+        }
+    }
+
+    /// Installs a filter reporting on the blocks from `cursor` on, on behalf of `owner`;
+    /// `Err` with the limit reached if there are too many filters already.
+    pub fn install(
+        &self,
+        kind: FilterKind,
+        cursor: u64,
+        owner: Option<ClientId>,
+    ) -> Result<FilterId, usize> {
+        let mut installed = self.installed.lock().expect("mutex.lock -> poisoned");
+        if installed.filters.len() >= self.max_filters {
+            return Err(self.max_filters);
+        }
+        if let Some(owner) = &owner {
+            let count = installed.per_client.entry(owner.clone()).or_default();
+            if *count >= self.max_filters_per_client {
+                return Err(self.max_filters_per_client);
+            }
+            *count += 1;
+        }
+
+        let id = FilterId::Str(B128::random().to_string());
+        installed.filters.insert(
+            id.clone(),
+            Installed {
+                kind,
+                cursor,
+                last_polled: Instant::now(),
+                owner,
+            },
+        );
+        metrics::gauge!("sequencer_filters_installed").set(installed.filters.len() as f64);
+        Ok(id)
+    }
+
+    pub fn uninstall(&self, id: &FilterId) -> bool {
+        let mut installed = self.installed.lock().expect("mutex.lock -> poisoned");
+        let uninstalled = installed.remove(id);
+        metrics::gauge!("sequencer_filters_installed").set(installed.filters.len() as f64);
+        uninstalled
+    }
+
+    /// What filter `id` has to report on, up to block `head`, which it is taken to have
+    /// reported on from then on; `None` if there is no such filter.
+    pub fn changes(&self, id: &FilterId, head: Option<u64>) -> Option<Changes> {
+        let mut installed = self.installed.lock().expect("mutex.lock -> poisoned");
+        let filter = installed.filters.get_mut(id)?;
+        filter.last_polled = Instant::now();
+
+        let from = filter.cursor;
+        let mut advance = || {
+            let to = head.filter(|head| *head >= from)?;
+            filter.cursor = to + 1;
+            Some(to)
+        };
+        let changes = match &mut filter.kind {
+            FilterKind::Logs(logs) => Changes::Logs {
+                filter: logs.clone(),
+                from,
+                to: advance(),
+            },
+            FilterKind::Blocks => Changes::Blocks {
+                from,
+                to: advance(),
+            },
+            FilterKind::PendingTransactions { full, accepted } => {
+                let mut transactions = vec![];
+                loop {
+                    match accepted.try_recv() {
+                        Ok(transaction) => transactions.push(transaction),
+                        Err(TryRecvError::Lagged(skipped)) => {
+                            tracing::debug!("filter {:?} skipped {} transactions", id, skipped);
+                        }
+                        Err(TryRecvError::Empty | TryRecvError::Closed) => break,
+                    }
+                }
+                Changes::PendingTransactions {
+                    full: *full,
+                    transactions,
+                }
+            }
+        };
+        Some(changes)
+    }
+
+    /// Takes back that filter `id` has reported on the blocks `from..=to`, which it failed to,
+    /// unless it has reported on later blocks since.
+    pub fn rewind(&self, id: &FilterId, from: u64, to: u64) {
+        let mut installed = self.installed.lock().expect("mutex.lock -> poisoned");
+        if let Some(filter) = installed.filters.get_mut(id) {
+            if filter.cursor == to + 1 {
+                filter.cursor = from;
+            }
+        }
+    }
+
+    /// The criteria of log-filter `id`; `None` if there is no such log-filter.
+    pub fn logs_filter(&self, id: &FilterId) -> Option<Filter> {
+        let mut installed = self.installed.lock().expect("mutex.lock -> poisoned");
+        let filter = installed.filters.get_mut(id)?;
+        filter.last_polled = Instant::now();
+        match &filter.kind {
+            FilterKind::Logs(logs) => Some(logs.clone()),
+            _ => None,
+        }
+    }
+
+    pub fn evict_idle(&self) {
+        let mut installed = self.installed.lock().expect("mutex.lock -> poisoned");
+        let idle: Vec<_> = installed
+            .filters
+            .iter()
+            .filter(|(_, filter)| filter.last_polled.elapsed() >= self.ttl)
+            .map(|(id, _)| id.clone())
+            .collect();
+        for id in &idle {
+            installed.remove(id);
+        }
+        metrics::gauge!("sequencer_filters_installed").set(installed.filters.len() as f64);
+    }
+}
+
+impl Installations {
+    fn remove(&mut self, id: &FilterId) -> bool {
+        let Some(filter) = self.filters.remove(id) else {
+            return false;
+        };
+        if let Some(owner) = filter.owner {
+            if let Entry::Occupied(mut count) = self.per_client.entry(owner) {
+                *count.get_mut() -= 1;
+                if *count.get() == 0 {
+                    count.remove();
+                }
+            }
+        }
+        true
+    }
+}
@@ -0,0 +1,206 @@
+use std::collections::VecDeque;
+use std::str::FromStr;
+use std::sync::RwLock;
+
+/// EIP-1559 parameters of the OP Stack since Canyon.
+const BASE_FEE_MAX_CHANGE_DENOMINATOR: u128 = 250;
+const ELASTICITY_MULTIPLIER: u128 = 6;
+
+/// How the suggested tip is derived from the tips paid in recent blocks.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Strategy {
+    /// The given percentile (0..=100) of the tips in the window.
+    Percentile(f64),
+    /// The highest tip in the window.
+    Max,
+}
+
+#[derive(Debug, Clone)]
+pub struct GasOracleConfig {
+    /// The number of most recent blocks the suggestions are based on.
+    pub window: usize,
+    pub strategy: Strategy,
+    /// The lowest tip ever suggested, in wei.
+    pub floor: u128,
+}
+
+/// Fee data of a single block.
+#[derive(Debug, Clone, Default)]
+pub struct BlockFees {
+    pub number: u64,
+    pub base_fee: u128,
+    pub gas_used: u128,
+    pub gas_limit: u128,
+    /// The effective tips of the block's transactions with their gas, sorted by tip.
+    pub tips: Vec<(u128, u64)>,
+}
+
+/// Keeps the fee data of the recent blocks and suggests fees based on it.
+#[derive(Debug)]
+pub struct GasOracle {
+    config: GasOracleConfig,
+    blocks: RwLock<VecDeque<BlockFees>>,
+}
+
+impl GasOracle {
+    pub fn new(config: GasOracleConfig) -> Self {
+        Self {
+            config,
+            blocks: Default::default(),
+        }
+    }
+
+    pub fn window(&self) -> usize {
+        self.config.window
+    }
+
+    /// Records `block`. Blocks are expected in ascending order;
+    /// a block at or below the latest known height replaces everything from that height on.
+    pub fn observe(&self, mut block: BlockFees) {
+        block.tips.sort_unstable();
+
+        let mut blocks = self.blocks.write().expect("rw-lock.write -> poisoned");
+        while blocks
+            .back()
+            .is_some_and(|last| last.number >= block.number)
+        {
+            blocks.pop_back();
+        }
+        if blocks
+            .back()
+            .is_some_and(|last| last.number + 1 != block.number)
+        {
+            blocks.clear();
+        }
+        blocks.push_back(block);
+        while blocks.len() > self.config.window {
+            blocks.pop_front();
+        }
+    }
+
+    pub fn latest(&self) -> Option<BlockFees> {
+        self.blocks
+            .read()
+            .expect("rw-lock.read -> poisoned")
+            .back()
+            .cloned()
+    }
+
+    /// The tip suggested by the recent blocks, or `None` if there is no data yet.
+    pub fn suggested_tip(&self) -> Option<u128> {
+        let blocks = self.blocks.read().expect("rw-lock.read -> poisoned");
+        if blocks.is_empty() {
+            return None;
+        }
+
+        let mut tips = blocks
+            .iter()
+            .flat_map(|block| block.tips.iter().map(|(tip, _)| *tip))
+            .collect::<Vec<_>>();
+        tips.sort_unstable();
+
+        let tip = match self.config.strategy {
+            Strategy::Max => tips.last().copied(),
+            Strategy::Percentile(percentile) => {
+                let idx = ((tips.len() as f64 - 1.0) * percentile / 100.0).round() as usize;
+                tips.get(idx).copied()
+            }
+        };
+        Some(tip.unwrap_or_default().max(self.config.floor))
+    }
+
+    /// The average number of transactions per block in the window.
+    pub fn transactions_per_block(&self) -> usize {
+        let blocks = self.blocks.read().expect("rw-lock.read -> poisoned");
+        if blocks.is_empty() {
+            return 0;
+        }
+        blocks.iter().map(|block| block.tips.len()).sum::<usize>() / blocks.len()
+    }
+
+    /// The fee data of the blocks `oldest..=newest`, if they all are in the window.
+    pub fn range(&self, oldest: u64, newest: u64) -> Option<Vec<BlockFees>> {
+        let blocks = self.blocks.read().expect("rw-lock.read -> poisoned");
+        let first = blocks.front()?.number;
+        let last = blocks.back()?.number;
+        if oldest < first || newest > last || oldest > newest {
+            return None;
+        }
+        Some(
+            blocks
+                .range((oldest - first) as usize..=(newest - first) as usize)
+                .cloned()
+                .collect(),
+        )
+    }
+}
+
+impl BlockFees {
+    pub fn gas_used_ratio(&self) -> f64 {
+        if self.gas_limit == 0 {
+            return 0.0;
+        }
+        self.gas_used as f64 / self.gas_limit as f64
+    }
+
+    /// The base fee of the block following this one.
+    pub fn next_base_fee(&self) -> u128 {
+        let gas_target = self.gas_limit / ELASTICITY_MULTIPLIER;
+        if gas_target == 0 || self.gas_used == gas_target {
+            return self.base_fee;
+        }
+        if self.gas_used > gas_target {
+            let delta = self.base_fee * (self.gas_used - gas_target)
+                / gas_target
+                / BASE_FEE_MAX_CHANGE_DENOMINATOR;
+            self.base_fee + delta.max(1)
+        } else {
+            let delta = self.base_fee * (gas_target - self.gas_used)
+                / gas_target
+                / BASE_FEE_MAX_CHANGE_DENOMINATOR;
+            self.base_fee.saturating_sub(delta)
+        }
+    }
+
+    /// The tip at `percentile` (0..=100), weighted by gas.
+    pub fn reward_at(&self, percentile: f64) -> u128 {
+        let total_gas = self.tips.iter().map(|(_, gas)| *gas).sum::<u64>();
+        if total_gas == 0 {
+            return 0;
+        }
+        let threshold = (total_gas as f64 * percentile / 100.0) as u64;
+        let mut cumulative = 0;
+        for (tip, gas) in self.tips.iter() {
+            cumulative += gas;
+            if cumulative >= threshold {
+                return *tip;
+            }
+        }
+        self.tips.last().map(|(tip, _)| *tip).unwrap_or_default()
+    }
+}
+
+/// Accepts `max`, `median` or `percentile:N`.
+impl FromStr for Strategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "max" => Ok(Self::Max),
+            "median" => Ok(Self::Percentile(50.0)),
+            _ => {
+                let percentile = s
+                    .strip_prefix("percentile:")
+                    .and_then(|p| p.parse::<f64>().ok())
+                    .filter(|p| (0.0..=100.0).contains(p))
+                    .ok_or_else(|| {
+                        format!(
+                            "expected `max`, `median` or `percentile:N` (0 <= N <= 100); got {:?}",
+                            s
+                        )
+                    })?;
+                Ok(Self::Percentile(percentile))
+            }
+        }
+    }
+}
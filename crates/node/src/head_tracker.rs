@@ -0,0 +1,148 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::RwLock;
+
+use alloy_primitives::B256;
+use alloy_rpc_types::Header;
+use tokio::sync::broadcast;
+
+/// The number of recent headers kept.
+pub const HEADER_CHAIN_DEPTH: u64 = 256;
+
+/// The number of new heads buffered for each of the subscribers.
+const NEW_HEADS_CAPACITY: usize = 64;
+
+/// Follows the canonical chain of the `default` backend: keeps its recent headers.
+#[derive(Debug)]
+pub struct HeadTracker {
+    headers: RwLock<BTreeMap<u64, Header>>,
+    new_heads: broadcast::Sender<Header>,
+    pushed: AtomicBool,
+}
+
+/// How a header relates to the tracked chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Link {
+    /// The header extends or replaces part of the chain, or there is nothing to link it to.
+    Linked,
+    /// The header's parent has to be applied first.
+    MissingParent(B256),
+}
+
+impl Default for HeadTracker {
+    fn default() -> Self {
+        Self {
+            headers: Default::default(),
+            new_heads: broadcast::channel(NEW_HEADS_CAPACITY).0,
+            pushed: Default::default(),
+        }
+    }
+}
+
+impl HeadTracker {
+    pub fn head(&self) -> Option<Header> {
+        self.headers
+            .read()
+            .expect("rw-lock.read -> poisoned")
+            .last_key_value()
+            .map(|(_, header)| header.clone())
+    }
+
+    pub fn head_number(&self) -> Option<u64> {
+        self.headers
+            .read()
+            .expect("rw-lock.read -> poisoned")
+            .last_key_value()
+            .map(|(number, _)| *number)
+    }
+
+    pub fn header(&self, number: u64) -> Option<Header> {
+        self.headers
+            .read()
+            .expect("rw-lock.read -> poisoned")
+            .get(&number)
+            .cloned()
+    }
+
+    /// The number of the kept header with `hash`.
+    pub fn number_of(&self, hash: &B256) -> Option<u64> {
+        self.headers
+            .read()
+            .expect("rw-lock.read -> poisoned")
+            .iter()
+            .rev()
+            .find(|(_, header)| header.hash.as_ref() == Some(hash))
+            .map(|(number, _)| *number)
+    }
+
+    /// The headers from `from` on, oldest first.
+    pub fn headers_from(&self, from: u64) -> Vec<Header> {
+        self.headers
+            .read()
+            .expect("rw-lock.read -> poisoned")
+            .range(from..)
+            .map(|(_, header)| header.clone())
+            .collect()
+    }
+
+    /// Receives every header applied from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<Header> {
+        self.new_heads.subscribe()
+    }
+
+    /// Whether the heads are pushed by a backend subscription rather than polled for.
+    pub fn is_pushed(&self) -> bool {
+        self.pushed.load(Ordering::Relaxed)
+    }
+
+    pub fn set_pushed(&self, pushed: bool) {
+        self.pushed.store(pushed, Ordering::Relaxed);
+    }
+
+    pub fn link(&self, number: u64, parent_hash: B256) -> Link {
+        let headers = self.headers.read().expect("rw-lock.read -> poisoned");
+        let Some((oldest, _)) = headers.first_key_value() else {
+            return Link::Linked;
+        };
+        let Some(parent_number) = number.checked_sub(1).filter(|n| n >= oldest) else {
+            return Link::Linked;
+        };
+        match headers.get(&parent_number) {
+            Some(parent) if parent.hash == Some(parent_hash) => Link::Linked,
+            _ if number > HEADER_CHAIN_DEPTH + *oldest => Link::Linked,
+            _ => Link::MissingParent(parent_hash),
+        }
+    }
+
+    /// Makes `header` the head: drops the headers it replaces and those older than the depth kept.
+    /// Returns the number of the first header replaced by another, if any.
+    pub fn apply(&self, number: u64, header: Header) -> Option<u64> {
+        let reorged_at = {
+            let mut headers = self.headers.write().expect("rw-lock.write -> poisoned");
+            let is_head = headers
+                .last_key_value()
+                .is_some_and(|(n, head)| *n == number && head.hash == header.hash);
+            if is_head {
+                return None;
+            }
+            let replaced = headers.split_off(&number);
+            let reorged_at = replaced
+                .iter()
+                .find(|(n, replaced)| **n > number || replaced.hash != header.hash)
+                .map(|(n, _)| *n);
+
+            headers.insert(number, header.clone());
+            let oldest_kept = number.saturating_sub(HEADER_CHAIN_DEPTH - 1);
+            *headers = headers.split_off(&oldest_kept);
+            reorged_at
+        };
+
+        metrics::gauge!("sequencer_head_block_number").set(number as f64);
+        if let Some(reorged_at) = reorged_at {
+            metrics::counter!("sequencer_reorgs_total").increment(1);
+            tracing::info!("reorg at #{}: new head #{}", reorged_at, number);
+        }
+        let _ = self.new_heads.send(header);
+        reorged_at
+    }
+}
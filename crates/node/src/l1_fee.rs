@@ -0,0 +1,228 @@
+pub use ::api::redstone::{GasEstimate, L1FeeEstimate};
+use alloy_primitives::U256;
+
+/// Fjord: `estimatedSize = max(MIN_TRANSACTION_SIZE, INTERCEPT + FASTLZ_COEF * fastlzSize)`,
+/// the coefficients being scaled by 1e6.
+const FJORD_INTERCEPT: i64 = -42_585_600;
+const FJORD_FASTLZ_COEF: i64 = 836_500;
+const FJORD_MIN_TRANSACTION_SIZE: i64 = 100;
+
+/// The size of a signature as added to an unsigned transaction by the `GasPriceOracle`.
+const SIGNATURE_SIZE: u64 = 68;
+
+/// The parameters of the L1 data fee as stored in the L1Block predeploy.
+#[derive(Debug, Clone, Default)]
+pub struct L1FeeParams {
+    pub l1_base_fee: U256,
+    pub blob_base_fee: U256,
+    pub base_fee_scalar: u32,
+    pub blob_base_fee_scalar: u32,
+    pub fjord: bool,
+}
+
+impl L1FeeParams {
+    /// The L1 data fee of the signed, enveloped transaction `transaction`.
+    pub fn estimate(&self, transaction: &[u8]) -> L1FeeEstimate {
+        if self.fjord {
+            self.fjord_estimate(flz_compress_len(transaction) as u64)
+        } else {
+            let calldata_gas = transaction
+                .iter()
+                .map(|b| if *b == 0 { 4 } else { 16 })
+                .sum::<u64>();
+            self.ecotone_estimate(calldata_gas)
+        }
+    }
+
+    /// An upper bound of the L1 data fee of a transaction whose unsigned encoding is
+    /// `unsigned_size` bytes long, assuming its contents are incompressible.
+    pub fn estimate_upper_bound(&self, unsigned_size: u64) -> L1FeeEstimate {
+        let size = unsigned_size + SIGNATURE_SIZE;
+        if self.fjord {
+            self.fjord_estimate(flz_upper_bound(size))
+        } else {
+            self.ecotone_estimate(size * 16)
+        }
+    }
+
+    fn fjord_estimate(&self, fastlz_size: u64) -> L1FeeEstimate {
+        let estimated_size = fjord_scaled_size(fastlz_size);
+        let l1_fee =
+            U256::from(estimated_size) * self.l1_fee_scaled() / U256::from(1_000_000_000_000u64);
+        self.to_estimate(l1_fee, estimated_size / 1_000_000)
+    }
+
+    fn ecotone_estimate(&self, calldata_gas: u64) -> L1FeeEstimate {
+        let l1_fee = U256::from(calldata_gas) * self.l1_fee_scaled() / U256::from(16_000_000u64);
+        self.to_estimate(l1_fee, calldata_gas / 16)
+    }
+
+    fn l1_fee_scaled(&self) -> U256 {
+        U256::from(self.base_fee_scalar) * U256::from(16) * self.l1_base_fee
+            + U256::from(self.blob_base_fee_scalar) * self.blob_base_fee
+    }
+
+    fn to_estimate(&self, l1_fee: U256, estimated_size: u64) -> L1FeeEstimate {
+        L1FeeEstimate {
+            l1_fee,
+            estimated_size,
+            l1_base_fee: self.l1_base_fee,
+            blob_base_fee: self.blob_base_fee,
+            base_fee_scalar: self.base_fee_scalar,
+            blob_base_fee_scalar: self.blob_base_fee_scalar,
+            fjord: self.fjord,
+        }
+    }
+}
+
+/// The estimated size of the signed, enveloped transaction `transaction`
+/// once compressed into a batch, in bytes, as per the Fjord cost model.
+pub fn estimated_da_size(transaction: &[u8]) -> u64 {
+    fjord_scaled_size(flz_compress_len(transaction) as u64) / 1_000_000
+}
+
+/// The Fjord size estimate, scaled by 1e6.
+fn fjord_scaled_size(fastlz_size: u64) -> u64 {
+    (FJORD_INTERCEPT + FJORD_FASTLZ_COEF * fastlz_size as i64)
+        .max(FJORD_MIN_TRANSACTION_SIZE * 1_000_000) as u64
+}
+
+/// An upper bound of the length of `size` bytes compressed with FastLZ: incompressible input
+/// takes a control byte per 32 literals.
+fn flz_upper_bound(size: u64) -> u64 {
+    size + size.div_ceil(32) + 16
+}
+
+/// The length of `input` compressed with FastLZ (level 1),
+/// as computed by the `GasPriceOracle` and the execution client since Fjord.
+pub fn flz_compress_len(input: &[u8]) -> u32 {
+    let mut n = 0u32;
+    let mut ht = vec![0u32; 8192];
+
+    let u24 = |i: u32| -> u32 {
+        let i = i as usize;
+        input[i] as u32 | (input[i + 1] as u32) << 8 | (input[i + 2] as u32) << 16
+    };
+    let cmp = |p: u32, q: u32, e: u32| -> u32 {
+        let mut l = 0u32;
+        let mut e = e - q;
+        while l < e {
+            if input[(p + l) as usize] != input[(q + l) as usize] {
+                e = 0;
+            }
+            l += 1;
+        }
+        l
+    };
+    let literals = |n: &mut u32, r: u32| {
+        *n += 0x21 * (r / 0x20);
+        let r = r % 0x20;
+        if r != 0 {
+            *n += r + 1;
+        }
+    };
+    let matched = |n: &mut u32, l: u32| {
+        let l = l - 1;
+        *n += 3 * (l / 262);
+        *n += if l % 262 >= 6 { 3 } else { 2 };
+    };
+    let hash = |v: u32| -> usize { ((2654435769u32.wrapping_mul(v) >> 19) & 0x1fff) as usize };
+
+    let mut a = 0u32;
+    let ip_limit = (input.len() as u32).saturating_sub(13);
+    let mut ip = a + 2;
+    while ip < ip_limit {
+        let mut r;
+        loop {
+            let s = u24(ip);
+            let h = hash(s);
+            r = ht[h];
+            ht[h] = ip;
+            let d = ip - r;
+            if ip >= ip_limit {
+                break;
+            }
+            ip += 1;
+            if d <= 0x1fff && s == u24(r) {
+                break;
+            }
+        }
+        if ip >= ip_limit {
+            break;
+        }
+        ip -= 1;
+        if ip > a {
+            literals(&mut n, ip - a);
+        }
+        let l = cmp(r + 3, ip + 3, ip_limit + 9);
+        matched(&mut n, l);
+        ip += l;
+        for _ in 0..2 {
+            ht[hash(u24(ip))] = ip;
+            ip += 1;
+        }
+        a = ip;
+    }
+    literals(&mut n, input.len() as u32 - a);
+    n
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::hex;
+
+    use super::*;
+
+    /// op-geth's `emptyTx`: an unsigned legacy transaction to `0x095e…2d87` without calldata.
+    const EMPTY_TX: [u8; 30] = hex!("dd80808094095e7baea6a6c7c4c2dfeb977efac326af552d878080808080");
+
+    /// The parameters of op-geth's cost-function tests.
+    fn params(fjord: bool) -> L1FeeParams {
+        L1FeeParams {
+            l1_base_fee: U256::from(1_000_000_000u64),
+            blob_base_fee: U256::from(10_000_000u64),
+            base_fee_scalar: 2,
+            blob_base_fee_scalar: 3,
+            fjord,
+        }
+    }
+
+    #[test]
+    fn flz_compress_len_matches_op_geth() {
+        assert_eq!(flz_compress_len(&[]), 0);
+        assert_eq!(flz_compress_len(&[1; 1000]), 21);
+        assert_eq!(flz_compress_len(&[0; 1000]), 21);
+        assert_eq!(flz_compress_len(&EMPTY_TX), 31);
+    }
+
+    #[test]
+    fn flz_upper_bound_holds_for_incompressible_input() {
+        // xorshift64, for random bytes that are the same on every run.
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        let calldata = (0..100_000)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state as u8
+            })
+            .collect::<Vec<_>>();
+        let compressed_len = flz_compress_len(&calldata) as u64;
+        assert!(compressed_len > calldata.len() as u64);
+        assert!(compressed_len <= flz_upper_bound(calldata.len() as u64));
+    }
+
+    #[test]
+    fn ecotone_l1_cost_matches_op_geth() {
+        let estimate = params(false).estimate(&EMPTY_TX);
+        assert_eq!(estimate.l1_fee, U256::from(960_900));
+        assert_eq!(estimate.estimated_size, 30);
+    }
+
+    #[test]
+    fn fjord_l1_cost_matches_op_geth() {
+        let estimate = params(true).estimate(&EMPTY_TX);
+        assert_eq!(estimate.l1_fee, U256::from(3_203_000));
+        assert_eq!(estimate.estimated_size, 100);
+    }
+}
@@ -1,4 +1,22 @@
 pub type AnyError = Box<dyn std::error::Error + Send + Sync + 'static>;
 
+pub mod admission;
 pub mod api;
 pub mod auth_layer;
+pub mod circuit_breaker;
+pub mod coalesce;
+pub mod error;
+pub mod filters;
+pub mod gas_oracle;
+pub mod head_tracker;
+pub mod l1_fee;
+pub mod logs;
+pub mod miner;
+pub mod pool;
+pub mod preflight;
+pub mod public_server;
+pub mod quota;
+pub mod rate_limit;
+pub mod response_cache;
+pub mod routing;
+pub mod signer;
@@ -0,0 +1,46 @@
+/// Bounds on the `eth_getLogs` queries a server answers.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LogsLimits {
+    /// The most blocks a query may span.
+    pub max_block_range: Option<u64>,
+    /// The most logs a query may return.
+    pub max_results: Option<usize>,
+}
+
+/// How the `eth_getLogs` queries spanning many blocks are fetched from the backend.
+#[derive(Debug, Clone, Copy)]
+pub struct LogsChunking {
+    /// Queries spanning more blocks are split into chunks of this many blocks; `None` disables it.
+    pub chunk_size: Option<u64>,
+    /// The most chunks of a query fetched at once.
+    pub parallelism: usize,
+}
+
+impl Default for LogsChunking {
+    fn default() -> Self {
+        Self {
+            chunk_size: None,
+            parallelism: 1,
+        }
+    }
+}
+
+impl LogsChunking {
+    /// The ranges of blocks `from..=to` is fetched as, in order; a single one if it is not split.
+    pub fn chunks(&self, from: u64, to: u64) -> Vec<(u64, u64)> {
+        let Some(size) = self.chunk_size.filter(|size| *size > 0) else {
+            return vec![(from, to)];
+        };
+        let mut chunks = vec![];
+        let mut start = from;
+        while start <= to {
+            let end = start.saturating_add(size - 1).min(to);
+            chunks.push((start, end));
+            if end == u64::MAX {
+                break;
+            }
+            start = end + 1;
+        }
+        chunks
+    }
+}
@@ -0,0 +1,185 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap, VecDeque};
+use std::sync::RwLock;
+
+pub use ::api::miner::MinerParams;
+use alloy_primitives::{Address, Bytes, U256, U64};
+use reth_primitives::TransactionSignedEcRecovered;
+
+use crate::admission::Rejection;
+use crate::l1_fee::estimated_da_size;
+use crate::pool::PooledTransaction;
+
+/// The longest extra-data allowed in a block header.
+pub const MAX_EXTRA_DATA_SIZE: usize = 32;
+
+#[derive(Debug, Default)]
+pub struct Miner {
+    params: RwLock<MinerParams>,
+}
+
+/// The room available to the sequencer's transactions in a block being built.
+#[derive(Debug, Clone, Copy)]
+pub struct BlockSpace {
+    pub gas: u64,
+    pub base_fee: u128,
+}
+
+impl Miner {
+    pub fn params(&self) -> MinerParams {
+        self.params
+            .read()
+            .expect("rw-lock.read -> poisoned")
+            .clone()
+    }
+
+    /// Zero means no limit.
+    pub fn set_max_da_size(&self, max_tx_size: u64, max_block_size: u64) {
+        self.modify(|params| {
+            params.max_tx_da_size = non_zero(max_tx_size);
+            params.max_block_da_size = non_zero(max_block_size);
+        });
+    }
+
+    /// Zero means no limit.
+    pub fn set_gas_price(&self, gas_price: U256) {
+        self.modify(|params| params.gas_price = Some(gas_price).filter(|p| !p.is_zero()));
+    }
+
+    /// Zero means no limit.
+    pub fn set_gas_limit(&self, gas_limit: u64) {
+        self.modify(|params| params.gas_limit = non_zero(gas_limit));
+    }
+
+    pub fn check_extra(&self, extra: &Bytes) -> Result<(), String> {
+        if extra.len() > MAX_EXTRA_DATA_SIZE {
+            return Err(format!(
+                "extra-data too long: {} > {}",
+                extra.len(),
+                MAX_EXTRA_DATA_SIZE
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn set_extra(&self, extra: Bytes) {
+        self.modify(|params| params.extra = extra);
+    }
+
+    /// The lowest priority fee accepted, if any.
+    pub fn min_tip(&self) -> Option<u128> {
+        self.params
+            .read()
+            .expect("rw-lock.read -> poisoned")
+            .gas_price
+            .map(|p| p.saturating_to())
+    }
+
+    /// Whether the sequencer has to pick the block's transactions itself rather than letting
+    /// the backend take them from its own pool: only the DA limits are beyond its miner.
+    pub fn shapes_payloads(&self) -> bool {
+        let params = self.params.read().expect("rw-lock.read -> poisoned");
+        params.max_tx_da_size.is_some() || params.max_block_da_size.is_some()
+    }
+
+    /// Whether `transaction` may be accepted under the current parameters.
+    ///
+    /// The DA limits are not checked: a transaction too large for a block today may fit once
+    /// they are raised, so it is left out when the block is built instead.
+    pub fn check(&self, transaction: &TransactionSignedEcRecovered) -> Result<(), Rejection> {
+        let params = self.params();
+
+        let tip = transaction.priority_fee_or_price();
+        if let Some(min) = params.gas_price.map(|p| p.saturating_to::<u128>()) {
+            if tip < min {
+                return Err(Rejection::Underpriced { tip, min });
+            }
+        }
+        Ok(())
+    }
+
+    fn modify(&self, f: impl FnOnce(&mut MinerParams)) {
+        let mut params = self.params.write().expect("rw-lock.write -> poisoned");
+        f(&mut params);
+        tracing::info!("miner parameters set: {:?}", *params);
+    }
+
+    /// Picks transactions from `pending` (by sender, ordered by nonce) for the next block:
+    /// the highest tips first, within `space` and the miner parameters.
+    ///
+    /// A sender whose transaction is skipped contributes nothing more, to avoid nonce-gaps.
+    /// `pending` must not hold transactions the parent block has included: they would take up
+    /// the space of others.
+    pub fn select(
+        &self,
+        pending: BTreeMap<Address, Vec<PooledTransaction>>,
+        space: BlockSpace,
+    ) -> Vec<Bytes> {
+        let params = self.params();
+        let min_tip = params.gas_price.map_or(0, |p| p.saturating_to::<u128>());
+        let max_tx_da_size = params.max_tx_da_size.map(|s| s.to::<u64>());
+        let max_block_da_size = params.max_block_da_size.map(|s| s.to::<u64>());
+
+        let mut queues = pending
+            .into_iter()
+            .map(|(sender, transactions)| (sender, VecDeque::from(transactions)))
+            .collect::<BTreeMap<_, _>>();
+        let mut heads = BinaryHeap::new();
+        for (sender, queue) in queues.iter() {
+            if let Some(head) = queue.front() {
+                if let Some(tip) = effective_tip(head, space.base_fee).filter(|t| *t >= min_tip) {
+                    heads.push((tip, Reverse(head.accepted_at), *sender));
+                }
+            }
+        }
+
+        let mut selected = vec![];
+        let mut gas_left = params
+            .gas_limit
+            .map_or(space.gas, |limit| space.gas.min(limit.to()));
+        let mut da_size_left = max_block_da_size.unwrap_or(u64::MAX);
+        while let Some((_, _, sender)) = heads.pop() {
+            let queue = queues.get_mut(&sender).expect("every head has a queue");
+            let transaction = queue.pop_front().expect("every head is in its queue");
+
+            let gas = transaction.transaction.gas_limit();
+            let da_size = estimated_da_size(&transaction.raw);
+            if gas > gas_left
+                || da_size > da_size_left
+                || max_tx_da_size.is_some_and(|limit| da_size > limit)
+            {
+                continue;
+            }
+
+            gas_left -= gas;
+            da_size_left -= da_size;
+            selected.push(transaction.raw);
+
+            if let Some(next) = queue.front() {
+                if let Some(tip) = effective_tip(next, space.base_fee).filter(|t| *t >= min_tip) {
+                    heads.push((tip, Reverse(next.accepted_at), sender));
+                }
+            }
+        }
+
+        metrics::gauge!("sequencer_payload_da_size")
+            .set((max_block_da_size.unwrap_or(u64::MAX) - da_size_left) as f64);
+        selected
+    }
+}
+
+/// The tip `transaction` pays at `base_fee`, or `None` if it cannot pay the base fee.
+fn effective_tip(transaction: &PooledTransaction, base_fee: u128) -> Option<u128> {
+    let max_fee = transaction.transaction.max_fee_per_gas();
+    let headroom = max_fee.checked_sub(base_fee)?;
+    Some(
+        transaction
+            .transaction
+            .priority_fee_or_price()
+            .min(headroom),
+    )
+}
+
+fn non_zero(value: u64) -> Option<U64> {
+    Some(U64::from(value)).filter(|v| !v.is_zero())
+}
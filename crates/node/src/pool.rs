@@ -0,0 +1,280 @@
+pub mod journal;
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::RwLock;
+use std::time::{Duration, SystemTime};
+
+use alloy_primitives::{Address, Bytes, B256};
+use reth_primitives::TransactionSignedEcRecovered;
+
+/// A transaction accepted by the sequencer and not yet seen included.
+#[derive(Debug, Clone)]
+pub struct PooledTransaction {
+    pub transaction: TransactionSignedEcRecovered,
+    pub raw: Bytes,
+    pub accepted_at: SystemTime,
+}
+
+/// The transactions accepted via `eth_sendRawTransaction`, by sender and nonce.
+#[derive(Debug, Default)]
+pub struct Pool {
+    inner: RwLock<Inner>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    by_sender: BTreeMap<Address, BTreeMap<u64, PooledTransaction>>,
+    by_hash: HashMap<B256, (Address, u64)>,
+    next_nonces: HashMap<Address, u64>,
+    /// Bumped on every change.
+    generation: u64,
+}
+
+/// The pool's transactions split into those that are executable right away ("pending")
+/// and those waiting behind a nonce-gap ("queued"), by sender and ordered by nonce.
+#[derive(Debug, Clone, Default)]
+pub struct PoolContent {
+    pub pending: BTreeMap<Address, Vec<PooledTransaction>>,
+    pub queued: BTreeMap<Address, Vec<PooledTransaction>>,
+}
+
+impl PooledTransaction {
+    pub fn new(transaction: TransactionSignedEcRecovered, raw: Bytes) -> Self {
+        Self {
+            transaction,
+            raw,
+            accepted_at: SystemTime::now(),
+        }
+    }
+
+    pub fn hash(&self) -> B256 {
+        self.transaction.hash()
+    }
+
+    pub fn sender(&self) -> Address {
+        self.transaction.signer()
+    }
+
+    pub fn nonce(&self) -> u64 {
+        self.transaction.nonce()
+    }
+}
+
+impl Pool {
+    /// Adds `transaction`, replacing the one with the same sender and nonce, if any.
+    pub fn insert(&self, transaction: PooledTransaction) -> Option<PooledTransaction> {
+        let mut inner = self.inner.write().expect("rw-lock.write -> poisoned");
+        let (hash, sender, nonce) = (
+            transaction.hash(),
+            transaction.sender(),
+            transaction.nonce(),
+        );
+
+        let replaced = inner
+            .by_sender
+            .entry(sender)
+            .or_default()
+            .insert(nonce, transaction);
+        if let Some(replaced) = replaced.as_ref() {
+            inner.by_hash.remove(&replaced.hash());
+        }
+        inner.by_hash.insert(hash, (sender, nonce));
+        inner.generation += 1;
+
+        metrics::gauge!("sequencer_pool_size").set(inner.by_hash.len() as f64);
+        replaced
+    }
+
+    pub fn get(&self, hash: &B256) -> Option<PooledTransaction> {
+        let inner = self.inner.read().expect("rw-lock.read -> poisoned");
+        let (sender, nonce) = inner.by_hash.get(hash)?;
+        inner.by_sender.get(sender)?.get(nonce).cloned()
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner
+            .read()
+            .expect("rw-lock.read -> poisoned")
+            .by_hash
+            .len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Changes whenever the pool does.
+    pub fn generation(&self) -> u64 {
+        self.inner
+            .read()
+            .expect("rw-lock.read -> poisoned")
+            .generation
+    }
+
+    pub fn senders(&self) -> Vec<Address> {
+        let inner = self.inner.read().expect("rw-lock.read -> poisoned");
+        inner.by_sender.keys().copied().collect()
+    }
+
+    /// All transactions, ordered by sender and nonce.
+    pub fn transactions(&self) -> Vec<PooledTransaction> {
+        let inner = self.inner.read().expect("rw-lock.read -> poisoned");
+        inner
+            .by_sender
+            .values()
+            .flat_map(|by_nonce| by_nonce.values().cloned())
+            .collect()
+    }
+
+    pub fn transactions_from(&self, sender: Address) -> Vec<PooledTransaction> {
+        let inner = self.inner.read().expect("rw-lock.read -> poisoned");
+        inner
+            .by_sender
+            .get(&sender)
+            .map(|by_nonce| by_nonce.values().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn content(&self) -> PoolContent {
+        let inner = self.inner.read().expect("rw-lock.read -> poisoned");
+        let mut content = PoolContent::default();
+        for (sender, by_nonce) in inner.by_sender.iter() {
+            let (pending, queued) = inner.split_at_gap(sender, by_nonce);
+            if !pending.is_empty() {
+                content.pending.insert(*sender, pending);
+            }
+            if !queued.is_empty() {
+                content.queued.insert(*sender, queued);
+            }
+        }
+        content
+    }
+
+    pub fn content_from(
+        &self,
+        sender: Address,
+    ) -> (Vec<PooledTransaction>, Vec<PooledTransaction>) {
+        let inner = self.inner.read().expect("rw-lock.read -> poisoned");
+        inner
+            .by_sender
+            .get(&sender)
+            .map(|by_nonce| inner.split_at_gap(&sender, by_nonce))
+            .unwrap_or_default()
+    }
+
+    /// Records `next_nonce` as the on-chain nonce of `sender`, if it has pooled transactions.
+    pub fn set_nonce(&self, sender: Address, next_nonce: u64) {
+        let mut inner = self.inner.write().expect("rw-lock.write -> poisoned");
+        if inner.by_sender.contains_key(&sender) {
+            inner.next_nonces.insert(sender, next_nonce);
+            inner.generation += 1;
+        }
+    }
+
+    /// Removes the transaction with `hash`, if pooled.
+    pub fn remove(&self, hash: &B256) -> Option<PooledTransaction> {
+        let mut inner = self.inner.write().expect("rw-lock.write -> poisoned");
+        let (sender, nonce) = inner.by_hash.remove(hash)?;
+        let by_nonce = inner.by_sender.get_mut(&sender)?;
+        let removed = by_nonce.remove(&nonce);
+        if by_nonce.is_empty() {
+            inner.by_sender.remove(&sender);
+            inner.next_nonces.remove(&sender);
+        }
+        inner.generation += 1;
+
+        metrics::gauge!("sequencer_pool_size").set(inner.by_hash.len() as f64);
+        removed
+    }
+
+    /// Removes the transactions of `sender` with nonces below `next_nonce`:
+    /// those have been included (or replaced by transactions that have been).
+    pub fn remove_included(&self, sender: Address, next_nonce: u64) -> usize {
+        let mut inner = self.inner.write().expect("rw-lock.write -> poisoned");
+        let Some(by_nonce) = inner.by_sender.get_mut(&sender) else {
+            return 0;
+        };
+        let pending = by_nonce.split_off(&next_nonce);
+        let included = std::mem::replace(by_nonce, pending);
+        if by_nonce.is_empty() {
+            inner.by_sender.remove(&sender);
+            inner.next_nonces.remove(&sender);
+        } else {
+            inner.next_nonces.insert(sender, next_nonce);
+        }
+        for transaction in included.values() {
+            inner.by_hash.remove(&transaction.hash());
+        }
+        inner.generation += 1;
+
+        metrics::gauge!("sequencer_pool_size").set(inner.by_hash.len() as f64);
+        included.len()
+    }
+
+    /// Removes the transactions that have been waiting for longer than `max_age`.
+    pub fn remove_expired(&self, max_age: Duration) -> usize {
+        let now = SystemTime::now();
+        let mut inner = self.inner.write().expect("rw-lock.write -> poisoned");
+        let Inner {
+            by_sender,
+            by_hash,
+            next_nonces,
+            generation,
+        } = &mut *inner;
+
+        let mut removed = 0;
+        by_sender.retain(|sender, by_nonce| {
+            by_nonce.retain(|_, transaction| {
+                let expired = now
+                    .duration_since(transaction.accepted_at)
+                    .is_ok_and(|age| age > max_age);
+                if expired {
+                    by_hash.remove(&transaction.hash());
+                    removed += 1;
+                }
+                !expired
+            });
+            if by_nonce.is_empty() {
+                next_nonces.remove(sender);
+            }
+            !by_nonce.is_empty()
+        });
+
+        if removed > 0 {
+            *generation += 1;
+        }
+
+        metrics::gauge!("sequencer_pool_size").set(by_hash.len() as f64);
+        removed
+    }
+}
+
+impl Inner {
+    /// The sender's transactions up to the first nonce-gap, and those after it.
+    ///
+    /// Unless the sender's on-chain nonce is known, its lowest pooled nonce is assumed to be next.
+    fn split_at_gap(
+        &self,
+        sender: &Address,
+        by_nonce: &BTreeMap<u64, PooledTransaction>,
+    ) -> (Vec<PooledTransaction>, Vec<PooledTransaction>) {
+        let mut expected_nonce = self
+            .next_nonces
+            .get(sender)
+            .copied()
+            .or_else(|| by_nonce.keys().next().copied())
+            .unwrap_or_default();
+
+        let mut pending = vec![];
+        let mut queued = vec![];
+        for (nonce, transaction) in by_nonce {
+            if queued.is_empty() && *nonce == expected_nonce {
+                pending.push(transaction.clone());
+                expected_nonce += 1;
+            } else {
+                queued.push(transaction.clone());
+            }
+        }
+        (pending, queued)
+    }
+}
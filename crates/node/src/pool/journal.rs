@@ -0,0 +1,81 @@
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use alloy_primitives::Bytes;
+
+use crate::AnyError;
+
+/// An append-only file of the raw transactions accepted into the [`Pool`](super::Pool),
+/// one hex-encoded transaction per line.
+#[derive(Debug)]
+pub struct Journal {
+    path: PathBuf,
+    file: Mutex<File>,
+}
+
+impl Journal {
+    pub fn open(path: PathBuf) -> Result<Self, AnyError> {
+        let file = open_for_append(&path)?;
+        Ok(Self {
+            path,
+            file: Mutex::new(file),
+        })
+    }
+
+    /// Reads the journaled transactions. Lines that fail to parse are skipped.
+    pub fn read(&self) -> Result<Vec<Bytes>, AnyError> {
+        let file = File::open(&self.path)?;
+        let mut transactions = vec![];
+        for (idx, line) in BufReader::new(file).lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            match line.parse::<Bytes>() {
+                Ok(raw) => transactions.push(raw),
+                Err(reason) => tracing::warn!(
+                    "skipping line #{} of the pool-journal {:?}: {}",
+                    idx + 1,
+                    self.path,
+                    reason
+                ),
+            }
+        }
+        Ok(transactions)
+    }
+
+    pub fn append(&self, raw: &Bytes) -> Result<(), AnyError> {
+        let mut file = self.file.lock().expect("mutex.lock -> poisoned");
+        writeln!(file, "{}", raw)?;
+        Ok(())
+    }
+
+    /// Replaces the journal's contents with the transactions produced by `snapshot`.
+    ///
+    /// `snapshot` is invoked with appends blocked, so that no transaction accepted concurrently
+    /// goes missing from the journal.
+    pub fn rewrite(&self, snapshot: impl FnOnce() -> Vec<Bytes>) -> Result<(), AnyError> {
+        let mut file = self.file.lock().expect("mutex.lock -> poisoned");
+
+        let tmp_path = self.path.with_extension("tmp");
+        let mut writer = BufWriter::new(File::create(&tmp_path)?);
+        for raw in snapshot() {
+            writeln!(writer, "{}", raw)?;
+        }
+        writer
+            .into_inner()
+            .map_err(|e| e.into_error())?
+            .sync_all()?;
+        std::fs::rename(&tmp_path, &self.path)?;
+
+        *file = open_for_append(&self.path)?;
+        Ok(())
+    }
+}
+
+fn open_for_append(path: &Path) -> Result<File, AnyError> {
+    Ok(OpenOptions::new().create(true).append(true).open(path)?)
+}
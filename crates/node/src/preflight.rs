@@ -0,0 +1,26 @@
+use alloy_primitives::B256;
+
+/// The Engine API methods served on server [A], as told to the engine endpoint.
+pub const ENGINE_CAPABILITIES: &[&str] = &[
+    "engine_newPayloadV1",
+    "engine_newPayloadV2",
+    "engine_newPayloadV3",
+    "engine_forkchoiceUpdatedV1",
+    "engine_forkchoiceUpdatedV2",
+    "engine_forkchoiceUpdatedV3",
+    "engine_getPayloadV1",
+    "engine_getPayloadV2",
+    "engine_getPayloadV3",
+    "engine_getPayloadBodiesByHashV1",
+    "engine_getPayloadBodiesByRangeV1",
+    "engine_exchangeTransitionConfigurationV1",
+];
+
+/// What the backends are checked against before the servers start.
+#[derive(Debug, Clone, Default)]
+pub struct Preflight {
+    pub chain_id: Option<u64>,
+    pub genesis_hash: Option<B256>,
+    /// The Engine API methods the engine endpoint must support.
+    pub engine_methods: Vec<String>,
+}
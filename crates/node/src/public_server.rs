@@ -0,0 +1,252 @@
+use std::convert::Infallible;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+
+use hyper::server::conn::AddrStream;
+use hyper::service::{make_service_fn, service_fn};
+use jsonrpsee::server::middleware::rpc::RpcServiceT;
+use jsonrpsee::server::{stop_channel, Methods, RpcServiceBuilder, ServerHandle};
+use jsonrpsee::types::Request;
+use tokio::task::futures::TaskLocalFuture;
+use tower::Service;
+
+use crate::quota::{QuotaLayer, Quotas};
+use crate::rate_limit::{ClientId, RateLimitLayer, RateLimiter};
+use crate::AnyError;
+
+const API_KEY_HEADER: &str = "x-api-key";
+const FORWARDED_FOR_HEADER: &str = "x-forwarded-for";
+
+tokio::task_local! {
+    static CLIENT: ClientId;
+}
+
+/// The client of the request being served on server [B]; `None` on server [A].
+pub fn current_client() -> Option<ClientId> {
+    CLIENT.try_with(ClientId::clone).ok()
+}
+
+/// The public-facing RPC-server ("server B"), over HTTP and WebSocket.
+///
+/// Unlike a plain [`jsonrpsee::server::Server`], this one knows who is calling:
+/// each request is served with a middleware stack built for its client.
+#[derive(Debug)]
+pub struct PublicServer {
+    bind_addr: SocketAddr,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    quotas: Option<Quotas>,
+    trust_forwarded_for: bool,
+    api_key_path_prefix: Option<String>,
+}
+
+impl PublicServer {
+    pub fn new(bind_addr: SocketAddr) -> Self {
+        Self {
+            bind_addr,
+            rate_limiter: None,
+            quotas: None,
+            trust_forwarded_for: false,
+            api_key_path_prefix: None,
+        }
+    }
+
+    pub fn with_rate_limiter(self, rate_limiter: Arc<RateLimiter>) -> Self {
+        Self {
+            rate_limiter: Some(rate_limiter),
+            ..self
+        }
+    }
+
+    pub fn with_quotas(self, quotas: Quotas) -> Self {
+        Self {
+            quotas: Some(quotas),
+            ..self
+        }
+    }
+
+    /// Take the client's IP-address from the `X-Forwarded-For` header when present: the last
+    /// address in it, as appended by the proxy. Only enable it when the server is right behind
+    /// a proxy that appends to this header.
+    pub fn with_trust_forwarded_for(self, trust_forwarded_for: bool) -> Self {
+        Self {
+            trust_forwarded_for,
+            ..self
+        }
+    }
+
+    /// Take the API-key from the URL-path too, as its segment following `prefix`
+    /// (e.g. `/v1` for `https://rpc.example/v1/<api-key>`).
+    pub fn with_api_key_path_prefix(self, prefix: Option<String>) -> Self {
+        Self {
+            api_key_path_prefix: prefix.map(|prefix| format!("{}/", prefix.trim_end_matches('/'))),
+            ..self
+        }
+    }
+
+    pub fn start(self, methods: impl Into<Methods>) -> Result<ServerHandle, AnyError> {
+        let Self {
+            bind_addr,
+            rate_limiter,
+            quotas,
+            trust_forwarded_for,
+            api_key_path_prefix,
+        } = self;
+        let methods: Methods = methods.into();
+
+        let (stop_handle, server_handle) = stop_channel();
+        let svc_builder = jsonrpsee::server::ServerBuilder::new().to_service_builder();
+
+        let stop_handle_for_service = stop_handle.clone();
+        let make_service = make_service_fn(move |conn: &AddrStream| {
+            let remote_addr = conn.remote_addr();
+            let stop_handle = stop_handle_for_service.clone();
+            let svc_builder = svc_builder.clone();
+            let methods = methods.clone();
+            let rate_limiter = rate_limiter.clone();
+            let quotas = quotas.clone();
+            let api_key_path_prefix = api_key_path_prefix.clone();
+
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request: hyper::Request<hyper::Body>| {
+                    let api_key = api_key(&request, api_key_path_prefix.as_deref());
+                    // Only a known API-key earns its own budget, any other is accounted by address.
+                    let known_api_key = api_key.clone().filter(|api_key| {
+                        quotas
+                            .as_ref()
+                            .is_some_and(|quotas| quotas.is_known(api_key))
+                    });
+                    let client =
+                        client_id(&request, known_api_key, remote_addr, trust_forwarded_for);
+                    let rpc_middleware = RpcServiceBuilder::new()
+                        .layer(ClientLayer(client.clone()))
+                        .option_layer(
+                            rate_limiter
+                                .clone()
+                                .map(|rate_limiter| RateLimitLayer::new(rate_limiter, client)),
+                        )
+                        .option_layer(
+                            quotas
+                                .clone()
+                                .map(|quotas| QuotaLayer::new(quotas, api_key)),
+                        );
+                    let mut service = svc_builder
+                        .clone()
+                        .set_rpc_middleware(rpc_middleware)
+                        .build(methods.clone(), stop_handle.clone());
+
+                    service.call(request)
+                }))
+            }
+        });
+
+        let server = hyper::Server::try_bind(&bind_addr)?.serve(make_service);
+        tokio::spawn(async move {
+            let graceful = server.with_graceful_shutdown(stop_handle.shutdown());
+            if let Err(reason) = graceful.await {
+                tracing::error!("RPC-server at {} failed: {}", bind_addr, reason);
+            }
+        });
+
+        Ok(server_handle)
+    }
+}
+
+/// Serves the requests with their client known to [`current_client`].
+#[derive(Debug, Clone)]
+struct ClientLayer(ClientId);
+
+impl<S> tower::Layer<S> for ClientLayer {
+    type Service = WithClient<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        WithClient {
+            inner,
+            client: self.0.clone(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct WithClient<S> {
+    inner: S,
+    client: ClientId,
+}
+
+impl<'a, S> RpcServiceT<'a> for WithClient<S>
+where
+    S: RpcServiceT<'a>,
+{
+    type Future = TaskLocalFuture<ClientId, S::Future>;
+
+    fn call(&self, request: Request<'a>) -> Self::Future {
+        CLIENT.scope(self.client.clone(), self.inner.call(request))
+    }
+}
+
+/// The API-key is taken from the `X-Api-Key` header, or else from the URL-path if it is
+/// `path_prefix` followed by a single segment.
+fn api_key<B>(request: &hyper::Request<B>, path_prefix: Option<&str>) -> Option<String> {
+    if let Some(api_key) = request
+        .headers()
+        .get(API_KEY_HEADER)
+        .and_then(|value| value.to_str().ok())
+    {
+        return Some(api_key.to_owned());
+    }
+
+    let api_key = request
+        .uri()
+        .path()
+        .strip_prefix(path_prefix?)?
+        .trim_end_matches('/');
+    (!api_key.is_empty() && !api_key.contains('/')).then(|| api_key.to_owned())
+}
+
+fn client_id<B>(
+    request: &hyper::Request<B>,
+    api_key: Option<String>,
+    remote_addr: SocketAddr,
+    trust_forwarded_for: bool,
+) -> ClientId {
+    if let Some(api_key) = api_key {
+        return ClientId::ApiKey(api_key);
+    }
+
+    let headers = request.headers();
+    // The addresses before the last one are the client's to make up.
+    let forwarded_for = trust_forwarded_for
+        .then(|| headers.get_all(FORWARDED_FOR_HEADER).iter().last())
+        .flatten()
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.rsplit(',').next())
+        .and_then(|last| last.trim().parse::<IpAddr>().ok());
+
+    ClientId::Ip(forwarded_for.unwrap_or(remote_addr.ip()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client_of(forwarded_for: &[&str]) -> ClientId {
+        let mut request = hyper::Request::builder();
+        for value in forwarded_for {
+            request = request.header(FORWARDED_FOR_HEADER, *value);
+        }
+        let request = request.body(()).unwrap();
+        client_id(&request, None, "10.0.0.1:8545".parse().unwrap(), true)
+    }
+
+    #[test]
+    fn the_client_is_the_last_forwarded_address() {
+        assert_eq!(
+            client_of(&["203.0.113.7, 198.51.100.2, 192.0.2.1"]),
+            ClientId::Ip("192.0.2.1".parse().unwrap())
+        );
+        assert_eq!(
+            client_of(&["203.0.113.7", "192.0.2.1"]),
+            ClientId::Ip("192.0.2.1".parse().unwrap())
+        );
+        assert_eq!(client_of(&[]), ClientId::Ip("10.0.0.1".parse().unwrap()));
+    }
+}
@@ -0,0 +1,375 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub use ::api::quota::{ApiKeyUsage, QuotaApiServer};
+use futures::future::{self, Either, Ready};
+use jsonrpsee::core::RpcResult;
+use jsonrpsee::server::middleware::rpc::RpcServiceT;
+use jsonrpsee::server::MethodResponse;
+use jsonrpsee::types::{ErrorObject, ErrorObjectOwned, Request};
+
+use crate::error::{ProxyError, RateLimit};
+use crate::AnyError;
+
+const MINUTE: u64 = 60;
+const DAY: u64 = 24 * 60 * 60;
+
+/// The contents of an API-keys file.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuotaConfig {
+    /// Reject requests without an API-key.
+    #[serde(default)]
+    pub require_api_key: bool,
+
+    /// The cost of a method that is absent in `method_costs`.
+    #[serde(default = "default_cost")]
+    pub default_cost: u64,
+
+    /// Compute-units charged per call, by method name.
+    #[serde(default)]
+    pub method_costs: HashMap<String, u64>,
+
+    #[serde(default)]
+    pub tiers: HashMap<String, Tier>,
+
+    #[serde(default)]
+    pub keys: HashMap<String, ApiKey>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Tier {
+    pub per_minute: Option<u64>,
+    pub per_day: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiKey {
+    pub tier: String,
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum QuotaError {
+    #[error("api-key required")]
+    MissingApiKey,
+    #[error("unknown api-key")]
+    UnknownApiKey,
+    #[error("{window} quota exceeded")]
+    Exceeded {
+        window: &'static str,
+        retry_after: Duration,
+    },
+}
+
+/// Per-API-key compute-unit accounting.
+#[derive(Debug, Clone)]
+pub struct Quotas(Arc<Inner>);
+
+#[derive(Debug)]
+struct Inner {
+    path: Option<PathBuf>,
+    config: RwLock<QuotaConfig>,
+    usage: Mutex<HashMap<String, Usage>>,
+}
+
+#[derive(Debug, Default)]
+struct Usage {
+    minute: u64,
+    minute_used: u64,
+    day: u64,
+    day_used: u64,
+    total_used: u64,
+}
+
+impl Quotas {
+    pub fn load(path: Option<PathBuf>) -> Result<Self, AnyError> {
+        let config = match path.as_deref() {
+            Some(path) => read_config(path)?,
+            None => Default::default(),
+        };
+        Ok(Self(Arc::new(Inner {
+            path,
+            config: RwLock::new(config),
+            usage: Default::default(),
+        })))
+    }
+
+    /// Takes the API-keys as given rather than from a file.
+    pub fn new(config: QuotaConfig) -> Result<Self, AnyError> {
+        config.validate()?;
+        Ok(Self(Arc::new(Inner {
+            path: None,
+            config: RwLock::new(config),
+            usage: Default::default(),
+        })))
+    }
+
+    /// Replaces the API-keys taken as given. The usage counters are kept.
+    pub fn reconfigure(&self, config: QuotaConfig) -> Result<(), AnyError> {
+        config.validate()?;
+        *self.0.config.write().expect("rw-lock.write -> poisoned") = config;
+        Ok(())
+    }
+
+    pub fn is_known(&self, api_key: &str) -> bool {
+        self.0
+            .config
+            .read()
+            .expect("rw-lock.read -> poisoned")
+            .keys
+            .contains_key(api_key)
+    }
+
+    /// Re-reads the API-keys file. The usage counters are kept.
+    pub fn reload(&self) -> Result<usize, AnyError> {
+        let Some(path) = self.0.path.as_deref() else {
+            return Ok(0);
+        };
+        let config = read_config(path)?;
+        let key_count = config.keys.len();
+        *self.0.config.write().expect("rw-lock.write -> poisoned") = config;
+        tracing::info!("reloaded {} api-keys from {:?}", key_count, path);
+        Ok(key_count)
+    }
+
+    /// Charges `api_key` for a call to `method`.
+    pub fn charge(&self, api_key: Option<&str>, method: &str) -> Result<(), QuotaError> {
+        let config = self.0.config.read().expect("rw-lock.read -> poisoned");
+        if !config.is_enabled() {
+            return Ok(());
+        }
+
+        let Some(api_key) = api_key else {
+            return if config.require_api_key {
+                Err(QuotaError::MissingApiKey)
+            } else {
+                Ok(())
+            };
+        };
+        let tier = config
+            .keys
+            .get(api_key)
+            .ok_or(QuotaError::UnknownApiKey)?
+            .tier
+            .as_str();
+        let limits = config.tiers.get(tier).cloned().unwrap_or_default();
+        let cost = config
+            .method_costs
+            .get(method)
+            .copied()
+            .unwrap_or(config.default_cost);
+
+        let now = unix_now();
+        let mut usage = self.0.usage.lock().expect("mutex.lock -> poisoned");
+        let usage = usage.entry(api_key.to_owned()).or_default();
+        usage.roll(now);
+
+        if let Some(limit) = limits.per_minute {
+            if usage.minute_used + cost > limit {
+                return Err(QuotaError::Exceeded {
+                    window: "minute",
+                    retry_after: Duration::from_secs(MINUTE - now % MINUTE),
+                });
+            }
+        }
+        if let Some(limit) = limits.per_day {
+            if usage.day_used + cost > limit {
+                return Err(QuotaError::Exceeded {
+                    window: "day",
+                    retry_after: Duration::from_secs(DAY - now % DAY),
+                });
+            }
+        }
+
+        usage.minute_used += cost;
+        usage.day_used += cost;
+        usage.total_used += cost;
+        metrics::counter!("sequencer_compute_units_total", "tier" => tier.to_owned())
+            .increment(cost);
+
+        Ok(())
+    }
+
+    pub fn usage(&self, api_key: Option<&str>) -> BTreeMap<String, ApiKeyUsage> {
+        let config = self.0.config.read().expect("rw-lock.read -> poisoned");
+        let mut usage = self.0.usage.lock().expect("mutex.lock -> poisoned");
+        let now = unix_now();
+
+        config
+            .keys
+            .iter()
+            .filter(|(key, _)| api_key.map_or(true, |api_key| api_key == key.as_str()))
+            .map(|(key, props)| {
+                let limits = config.tiers.get(&props.tier).cloned().unwrap_or_default();
+                let usage = usage.entry(key.clone()).or_default();
+                usage.roll(now);
+
+                let report = ApiKeyUsage {
+                    tier: props.tier.clone(),
+                    minute_used: usage.minute_used,
+                    minute_limit: limits.per_minute,
+                    day_used: usage.day_used,
+                    day_limit: limits.per_day,
+                    total_used: usage.total_used,
+                };
+                (key.clone(), report)
+            })
+            .collect()
+    }
+}
+
+impl QuotaConfig {
+    /// Whether there are API-keys to check, or they are required.
+    fn is_enabled(&self) -> bool {
+        self.require_api_key || !self.keys.is_empty()
+    }
+
+    /// Fails if a key is of a tier that is not defined.
+    pub fn validate(&self) -> Result<(), AnyError> {
+        let mut keys = self.keys.iter().collect::<Vec<_>>();
+        keys.sort_unstable_by_key(|(key, _)| key.as_str());
+        match keys
+            .into_iter()
+            .find(|(_, props)| !self.tiers.contains_key(&props.tier))
+        {
+            Some((key, props)) => Err(format!(
+                "api-key {:?} is of unknown tier {:?}",
+                props.label.as_deref().unwrap_or(key),
+                props.tier
+            )
+            .into()),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Usage {
+    fn roll(&mut self, now: u64) {
+        if self.minute != now / MINUTE {
+            self.minute = now / MINUTE;
+            self.minute_used = 0;
+        }
+        if self.day != now / DAY {
+            self.day = now / DAY;
+            self.day_used = 0;
+        }
+    }
+}
+
+impl QuotaError {
+    pub fn to_error_object(&self) -> ErrorObjectOwned {
+        let error = match self {
+            Self::MissingApiKey | Self::UnknownApiKey => ProxyError::Unauthorized {
+                reason: self.to_string(),
+            },
+            Self::Exceeded {
+                window,
+                retry_after,
+            } => ProxyError::RateLimited {
+                limit: RateLimit::Quota(window),
+                retry_after: *retry_after,
+            },
+        };
+        error.into()
+    }
+}
+
+#[async_trait::async_trait]
+impl QuotaApiServer for Quotas {
+    async fn api_key_usage(
+        &self,
+        api_key: Option<String>,
+    ) -> RpcResult<BTreeMap<String, ApiKeyUsage>> {
+        Ok(self.usage(api_key.as_deref()))
+    }
+
+    async fn api_keys_reload(&self) -> RpcResult<usize> {
+        self.reload().map_err(|reason| {
+            ErrorObject::owned(
+                jsonrpsee::types::error::INTERNAL_ERROR_CODE,
+                reason.to_string(),
+                None::<()>,
+            )
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct QuotaLayer {
+    quotas: Quotas,
+    api_key: Option<String>,
+}
+
+impl QuotaLayer {
+    pub fn new(quotas: Quotas, api_key: Option<String>) -> Self {
+        Self { quotas, api_key }
+    }
+}
+
+impl<S> tower::Layer<S> for QuotaLayer {
+    type Service = Quota<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Quota {
+            inner,
+            quotas: self.quotas.clone(),
+            api_key: self.api_key.clone(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Quota<S> {
+    inner: S,
+    quotas: Quotas,
+    api_key: Option<String>,
+}
+
+impl<'a, S> RpcServiceT<'a> for Quota<S>
+where
+    S: RpcServiceT<'a>,
+{
+    type Future = Either<S::Future, Ready<MethodResponse>>;
+
+    fn call(&self, request: Request<'a>) -> Self::Future {
+        match self
+            .quotas
+            .charge(self.api_key.as_deref(), request.method_name())
+        {
+            Ok(()) => Either::Left(self.inner.call(request)),
+            Err(reason) => {
+                metrics::counter!("sequencer_quota_rejected_total").increment(1);
+                Either::Right(future::ready(MethodResponse::error(
+                    request.id,
+                    reason.to_error_object(),
+                )))
+            }
+        }
+    }
+}
+
+fn read_config(path: &Path) -> Result<QuotaConfig, AnyError> {
+    let config: QuotaConfig = serde_json::from_slice(&std::fs::read(path)?)
+        .map_err(|reason| format!("invalid api-keys file {:?}: {}", path, reason))?;
+    config
+        .validate()
+        .map_err(|reason| format!("invalid api-keys file {:?}: {}", path, reason))?;
+    Ok(config)
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system clock is before UNIX_EPOCH")
+        .as_secs()
+}
+
+fn default_cost() -> u64 {
+    1
+}
@@ -0,0 +1,261 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use futures::future::{self, Either, Ready};
+use jsonrpsee::server::middleware::rpc::RpcServiceT;
+use jsonrpsee::server::MethodResponse;
+use jsonrpsee::types::Request;
+
+use crate::error::{ProxyError, RateLimit};
+
+/// Methods that submit transactions.
+const WRITE_METHODS: &[&str] = &["eth_sendRawTransaction", "eth_sendTransaction"];
+
+/// Read methods that are notably more expensive for the backend than the rest.
+const EXPENSIVE_METHODS: &[&str] = &[
+    "eth_call",
+    "eth_estimateGas",
+    "eth_getLogs",
+    "eth_getProof",
+    "redstone_estimateGas",
+    "redstone_estimateL1Fee",
+];
+
+/// The identity a rate-limit budget is accounted against.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ClientId {
+    Ip(IpAddr),
+    ApiKey(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MethodClass {
+    Write,
+    Expensive,
+    Other,
+}
+
+/// A token-bucket: `rate` requests per second on average, at most `burst` at once.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Budget {
+    pub rate: f64,
+    pub burst: f64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Budgets {
+    pub write: Option<Budget>,
+    pub expensive: Option<Budget>,
+    pub other: Option<Budget>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RateLimitConfig {
+    pub per_ip: Budgets,
+    pub per_api_key: Budgets,
+}
+
+#[derive(Debug)]
+pub struct RateLimiter {
+    config: RwLock<RateLimitConfig>,
+    buckets: Mutex<HashMap<(ClientId, MethodClass), Bucket>>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl MethodClass {
+    pub fn of(method: &str) -> Self {
+        if WRITE_METHODS.contains(&method) {
+            Self::Write
+        } else if EXPENSIVE_METHODS.contains(&method) {
+            Self::Expensive
+        } else {
+            Self::Other
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Write => "write",
+            Self::Expensive => "expensive",
+            Self::Other => "other",
+        }
+    }
+}
+
+impl Budgets {
+    fn get(&self, class: MethodClass) -> Option<Budget> {
+        match class {
+            MethodClass::Write => self.write,
+            MethodClass::Expensive => self.expensive,
+            MethodClass::Other => self.other,
+        }
+    }
+}
+
+impl RateLimitConfig {
+    pub fn is_enabled(&self) -> bool {
+        [&self.per_ip, &self.per_api_key]
+            .into_iter()
+            .any(|b| b.write.is_some() || b.expensive.is_some() || b.other.is_some())
+    }
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config: RwLock::new(config),
+            buckets: Default::default(),
+        }
+    }
+
+    /// Replaces the budgets. The buckets are kept, and capped to the new bursts as they refill.
+    pub fn reconfigure(&self, config: RateLimitConfig) {
+        *self.config.write().expect("rw-lock.write -> poisoned") = config;
+    }
+
+    /// Takes a token for `method` out of `client`'s budget.
+    /// Returns the time after which a retry may succeed if the budget is exhausted.
+    pub fn check(&self, client: &ClientId, method: &str) -> Result<(), Duration> {
+        let class = MethodClass::of(method);
+        let config = self.config.read().expect("rw-lock.read -> poisoned");
+        let budgets = match client {
+            ClientId::Ip(_) => &config.per_ip,
+            ClientId::ApiKey(_) => &config.per_api_key,
+        };
+        let Some(budget) = budgets.get(class) else {
+            return Ok(());
+        };
+
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().expect("mutex.lock -> poisoned");
+        let bucket = buckets
+            .entry((client.clone(), class))
+            .or_insert_with(|| Bucket {
+                tokens: budget.burst,
+                updated_at: now,
+            });
+
+        bucket.tokens = (bucket.tokens
+            + now.duration_since(bucket.updated_at).as_secs_f64() * budget.rate)
+            .min(budget.burst);
+        bucket.updated_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / budget.rate))
+        }
+    }
+
+    /// Forgets the buckets that have been idle for long enough to be full again.
+    pub fn evict_idle(&self) {
+        let now = Instant::now();
+        let config = self.config.read().expect("rw-lock.read -> poisoned");
+        let mut buckets = self.buckets.lock().expect("mutex.lock -> poisoned");
+        buckets.retain(|(client, class), bucket| {
+            let budgets = match client {
+                ClientId::Ip(_) => &config.per_ip,
+                ClientId::ApiKey(_) => &config.per_api_key,
+            };
+            budgets.get(*class).is_some_and(|budget| {
+                let refill = now.duration_since(bucket.updated_at).as_secs_f64() * budget.rate;
+                bucket.tokens + refill < budget.burst
+            })
+        });
+    }
+}
+
+/// Accepts `RATE` or `RATE:BURST`, where `RATE` is in requests per second.
+impl FromStr for Budget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (rate, burst) = match s.split_once(':') {
+            Some((rate, burst)) => (rate, Some(burst)),
+            None => (s, None),
+        };
+        let rate: f64 = rate
+            .trim()
+            .parse()
+            .map_err(|e| format!("invalid rate {:?}: {}", rate, e))?;
+        let burst: f64 = match burst {
+            Some(burst) => burst
+                .trim()
+                .parse()
+                .map_err(|e| format!("invalid burst {:?}: {}", burst, e))?,
+            None => rate.max(1.0),
+        };
+        if rate.is_nan() || rate <= 0.0 || burst.is_nan() || burst < 1.0 {
+            return Err(format!(
+                "invalid budget {:?}: rate must be > 0, burst >= 1",
+                s
+            ));
+        }
+        Ok(Self { rate, burst })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RateLimitLayer {
+    limiter: Arc<RateLimiter>,
+    client: ClientId,
+}
+
+impl RateLimitLayer {
+    pub fn new(limiter: Arc<RateLimiter>, client: ClientId) -> Self {
+        Self { limiter, client }
+    }
+}
+
+impl<S> tower::Layer<S> for RateLimitLayer {
+    type Service = RateLimit<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimit {
+            inner,
+            limiter: Arc::clone(&self.limiter),
+            client: self.client.clone(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct RateLimit<S> {
+    inner: S,
+    limiter: Arc<RateLimiter>,
+    client: ClientId,
+}
+
+impl<'a, S> RpcServiceT<'a> for RateLimit<S>
+where
+    S: RpcServiceT<'a>,
+{
+    type Future = Either<S::Future, Ready<MethodResponse>>;
+
+    fn call(&self, request: Request<'a>) -> Self::Future {
+        let method = request.method_name();
+        match self.limiter.check(&self.client, method) {
+            Ok(()) => Either::Left(self.inner.call(request)),
+            Err(retry_after) => {
+                let class = MethodClass::of(method);
+                metrics::counter!("sequencer_rate_limited_total", "class" => class.as_str())
+                    .increment(1);
+
+                let error = ProxyError::RateLimited {
+                    limit: RateLimit::Class(class.as_str()),
+                    retry_after,
+                };
+                Either::Right(future::ready(MethodResponse::error(request.id, error)))
+            }
+        }
+    }
+}
@@ -0,0 +1,239 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use alloy_primitives::B256;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+/// How long a result referring to a block that is not final yet may be served from the cache.
+const UNSAFE_ENTRY_TTL: Duration = Duration::from_secs(30);
+
+/// For how long a cached result holds.
+#[derive(Debug, Clone, Copy)]
+pub enum Lifetime {
+    /// The result never changes, e.g. the code at a block referred to by hash.
+    Forever,
+    /// The result holds while the block it refers to stays canonical.
+    /// The block is taken from the result's fields `number` and `hash`
+    /// (of its first element, if the result is an array).
+    WhileCanonical {
+        number: &'static str,
+        hash: &'static str,
+    },
+}
+
+/// A bounded, least-recently-used cache of the results of the immutable calls.
+#[derive(Debug)]
+pub struct ResponseCache {
+    capacity: usize,
+    inner: Mutex<Inner>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    entries: HashMap<String, Entry>,
+    recency: BTreeMap<u64, String>,
+    tick: u64,
+    finalized: u64,
+    /// The hashes of the non-final canonical blocks, by number.
+    canonical: BTreeMap<u64, B256>,
+}
+
+#[derive(Debug)]
+struct Entry {
+    value: Value,
+    block: Option<u64>,
+    inserted_at: Instant,
+    used_at: u64,
+}
+
+impl ResponseCache {
+    /// A cache of at most `capacity` results; zero disables it.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            inner: Default::default(),
+        }
+    }
+
+    pub fn get<T: DeserializeOwned>(&self, method: &str, params: &impl Serialize) -> Option<T> {
+        if self.capacity == 0 {
+            return None;
+        }
+        let key = key(method, params)?;
+
+        let mut inner = self.inner.lock().expect("mutex.lock -> poisoned");
+        let Inner {
+            entries,
+            recency,
+            tick,
+            finalized,
+            ..
+        } = &mut *inner;
+
+        let Some(entry) = entries.get_mut(&key) else {
+            metrics::counter!("sequencer_response_cache_total", "result" => "miss").increment(1);
+            return None;
+        };
+        let unsafe_expired = entry.block.is_some_and(|block| block > *finalized)
+            && entry.inserted_at.elapsed() > UNSAFE_ENTRY_TTL;
+        if unsafe_expired {
+            recency.remove(&entry.used_at);
+            entries.remove(&key);
+            metrics::counter!("sequencer_response_cache_total", "result" => "miss").increment(1);
+            return None;
+        }
+
+        *tick += 1;
+        recency.remove(&entry.used_at);
+        recency.insert(*tick, key);
+        entry.used_at = *tick;
+
+        metrics::counter!("sequencer_response_cache_total", "result" => "hit").increment(1);
+        serde_json::from_value(entry.value.clone()).ok()
+    }
+
+    /// Caches `value` unless it is empty or refers to a block known not to be canonical.
+    pub fn insert(
+        &self,
+        method: &str,
+        params: &impl Serialize,
+        value: &impl Serialize,
+        lifetime: Lifetime,
+    ) {
+        self.store(method, params, value, lifetime, false)
+    }
+
+    /// As [`Self::insert`], but an empty array is cached too: for the results where it is as
+    /// final as any other, such as the logs of finalized blocks.
+    pub fn insert_even_if_empty(
+        &self,
+        method: &str,
+        params: &impl Serialize,
+        value: &impl Serialize,
+        lifetime: Lifetime,
+    ) {
+        self.store(method, params, value, lifetime, true)
+    }
+
+    fn store(
+        &self,
+        method: &str,
+        params: &impl Serialize,
+        value: &impl Serialize,
+        lifetime: Lifetime,
+        keep_empty: bool,
+    ) {
+        if self.capacity == 0 {
+            return;
+        }
+        let (Some(key), Ok(value)) = (key(method, params), serde_json::to_value(value)) else {
+            return;
+        };
+        let empty = value.as_array().is_some_and(Vec::is_empty);
+        if value.is_null() || (empty && !keep_empty) {
+            return;
+        }
+        let block = match lifetime {
+            Lifetime::Forever => None,
+            Lifetime::WhileCanonical { number, hash } => match block_of(&value, number, hash) {
+                Some(block) => Some(block),
+                None => return,
+            },
+        };
+
+        let mut inner = self.inner.lock().expect("mutex.lock -> poisoned");
+        if let Some((number, hash)) = block {
+            if inner
+                .canonical
+                .get(&number)
+                .is_some_and(|canonical| *canonical != hash)
+            {
+                return;
+            }
+        }
+
+        inner.tick += 1;
+        let used_at = inner.tick;
+        if let Some(replaced) = inner.entries.insert(
+            key.clone(),
+            Entry {
+                value,
+                block: block.map(|(number, _)| number),
+                inserted_at: Instant::now(),
+                used_at,
+            },
+        ) {
+            inner.recency.remove(&replaced.used_at);
+        }
+        inner.recency.insert(used_at, key);
+
+        while inner.entries.len() > self.capacity {
+            let Some((_, oldest)) = inner.recency.pop_first() else {
+                break;
+            };
+            inner.entries.remove(&oldest);
+        }
+        metrics::gauge!("sequencer_response_cache_size").set(inner.entries.len() as f64);
+    }
+
+    pub fn finalized(&self) -> u64 {
+        self.inner.lock().expect("mutex.lock -> poisoned").finalized
+    }
+
+    pub fn set_finalized(&self, finalized: u64) {
+        let mut inner = self.inner.lock().expect("mutex.lock -> poisoned");
+        inner.finalized = inner.finalized.max(finalized);
+        let unsafe_from = inner.finalized + 1;
+        inner.canonical = inner.canonical.split_off(&unsafe_from);
+    }
+
+    /// Records `hash` as the canonical block at `number`.
+    /// If another block was canonical there, drops every result referring to `number` or above.
+    pub fn observe_canonical(&self, number: u64, hash: B256) {
+        let mut inner = self.inner.lock().expect("mutex.lock -> poisoned");
+        if number <= inner.finalized {
+            return;
+        }
+        let reorged = inner
+            .canonical
+            .get(&number)
+            .is_some_and(|canonical| *canonical != hash);
+        if reorged {
+            inner.canonical.retain(|canonical, _| *canonical < number);
+            let Inner {
+                entries, recency, ..
+            } = &mut *inner;
+            entries.retain(|_, entry| {
+                let stale = entry.block.is_some_and(|block| block >= number);
+                if stale {
+                    recency.remove(&entry.used_at);
+                }
+                !stale
+            });
+            tracing::info!("reorg at #{}: dropped the cached results above", number);
+        }
+        inner.canonical.insert(number, hash);
+    }
+}
+
+fn key(method: &str, params: &impl Serialize) -> Option<String> {
+    Some(format!(
+        "{}:{}",
+        method,
+        serde_json::to_string(params).ok()?
+    ))
+}
+
+fn block_of(value: &Value, number: &str, hash: &str) -> Option<(u64, B256)> {
+    let object = match value {
+        Value::Array(elements) => elements.first()?,
+        _ => value,
+    };
+    let number = object.get(number)?.as_str()?;
+    let number = u64::from_str_radix(number.strip_prefix("0x")?, 16).ok()?;
+    let hash = object.get(hash)?.as_str()?.parse().ok()?;
+    Some((number, hash))
+}
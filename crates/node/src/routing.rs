@@ -0,0 +1,699 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use alloy_primitives::B64;
+use alloy_rpc_types::{BlockId, BlockNumberOrTag};
+use jsonrpsee::core::{ClientError, RpcResult};
+use jsonrpsee::http_client::transport::HttpBackend;
+use jsonrpsee::http_client::HttpClient;
+use reth_rpc_api::EthApiClient;
+
+use crate::circuit_breaker::{BreakerConfig, CircuitBreaker};
+use crate::error::{is_request_too_large, ProxyError};
+use crate::AnyError;
+
+/// The name of the backend given by `BACKEND_ETH_API_URL`.
+pub const DEFAULT_BACKEND: &str = "default";
+
+/// The weight of the latest sample in a replica's average latency.
+const LATENCY_EWMA_WEIGHT: f64 = 0.2;
+
+/// The contents of a routing-file.
+///
+/// A method is routed by the first of these that applies:
+/// - `historical`, if the method refers to a block deep enough below the head;
+/// - `routes`, by the method's full name;
+/// - `routes`, by the method's namespace (e.g. `eth`);
+/// - the `default` backend.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct RoutingConfig {
+    /// Backends by name. The `default` backend takes its `url` from `BACKEND_ETH_API_URL`,
+    /// but may be given replicas here.
+    #[serde(default)]
+    pub backends: BTreeMap<String, BackendConfig>,
+
+    /// Backend names by method name or namespace.
+    #[serde(default)]
+    pub routes: BTreeMap<String, String>,
+
+    #[serde(default)]
+    pub historical: Option<HistoricalRoute>,
+
+    /// Timeouts in milliseconds by method name or namespace, overriding the default timeout.
+    #[serde(default)]
+    pub timeouts_ms: BTreeMap<String, u64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct BackendConfig {
+    /// The primary node: it keeps the state of the filters and receives the transactions.
+    #[serde(default)]
+    pub url: Option<String>,
+
+    /// Further nodes the reads are spread across.
+    #[serde(default)]
+    pub replicas: Vec<String>,
+
+    #[serde(default)]
+    pub balance: Balance,
+
+    /// Nodes lagging behind the sequencer's head by more than this are not read from.
+    #[serde(default = "default_max_lag")]
+    pub max_lag: u64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Balance {
+    #[default]
+    RoundRobin,
+    LeastLatency,
+}
+
+/// Where to send the requests for blocks that are more than `depth` blocks below the head,
+/// and for `earliest`. A block referred to by hash only goes there if it is among the recent
+/// headers kept, and is that old.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct HistoricalRoute {
+    pub backend: String,
+    #[serde(default = "default_historical_depth")]
+    pub depth: u64,
+}
+
+/// How the requests to the backends are timed out and retried.
+#[derive(Debug, Clone)]
+pub struct CallPolicy {
+    pub timeout: Duration,
+    /// Timeouts by method name or namespace, overriding `timeout`.
+    pub timeouts: BTreeMap<String, Duration>,
+    /// The times a read that failed for want of an answer is retried.
+    pub retries: u32,
+    /// The delay before the first retry; it doubles with each retry, and is jittered.
+    pub retry_backoff: Duration,
+    pub breaker: BreakerConfig,
+}
+
+impl Default for CallPolicy {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(60),
+            timeouts: Default::default(),
+            retries: 0,
+            retry_backoff: Duration::from_millis(100),
+            breaker: Default::default(),
+        }
+    }
+}
+
+impl CallPolicy {
+    /// The timeout of `method`: by its full name, by its namespace, or the default one.
+    pub fn timeout(&self, method: &str) -> Duration {
+        let namespace = method.split_once('_').map(|(namespace, _)| namespace);
+        self.timeouts
+            .get(method)
+            .or_else(|| namespace.and_then(|namespace| self.timeouts.get(namespace)))
+            .copied()
+            .unwrap_or(self.timeout)
+    }
+
+    /// The delay before the `retry`-th retry: half of it fixed, the other half random.
+    fn backoff(&self, retry: u32) -> Duration {
+        let backoff = self
+            .retry_backoff
+            .saturating_mul(1 << retry.saturating_sub(1).min(16));
+        let jitter = (u64::from_be_bytes(B64::random().0) >> 11) as f64 / (1u64 << 53) as f64;
+        backoff / 2 + backoff.mul_f64(jitter / 2.0)
+    }
+
+    fn max_timeout(&self) -> Duration {
+        self.timeouts
+            .values()
+            .copied()
+            .fold(self.timeout, Duration::max)
+    }
+}
+
+/// A named group of nodes serving the same data: the primary and its replicas.
+#[derive(Debug)]
+pub struct Backend {
+    name: String,
+    replicas: Vec<Replica>,
+    balance: Balance,
+    max_lag: u64,
+    next: AtomicUsize,
+    sequencer_head: Arc<AtomicU64>,
+    policy: Arc<CallPolicy>,
+    /// Guards the reads, which clients choose the cost of, apart from the writes: a slow query
+    /// must not keep the transactions from being submitted.
+    read_breaker: CircuitBreaker,
+    write_breaker: CircuitBreaker,
+}
+
+/// The backend picked for a method.
+#[derive(Debug, Clone, Copy)]
+pub struct Route<'a> {
+    pub backend: &'a Backend,
+    pub method: &'a str,
+    /// The lowest head of the nodes the request may be read from.
+    pub min_head: u64,
+}
+
+#[derive(Debug)]
+struct Replica {
+    url: String,
+    client: HttpClient<HttpBackend>,
+    healthy: AtomicBool,
+    head: AtomicU64,
+    latency_micros: AtomicU64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RequestKind {
+    Read,
+    /// A request that may not be idempotent, or that submits a transaction.
+    Write,
+}
+
+/// Picks the backend that serves a request.
+#[derive(Debug)]
+pub struct Router {
+    backends: BTreeMap<String, Backend>,
+    routes: BTreeMap<String, String>,
+    historical: Option<HistoricalRoute>,
+    /// The head of the sequencer, as followed by its head tracker.
+    sequencer_head: Arc<AtomicU64>,
+}
+
+impl Backend {
+    fn new(
+        name: &str,
+        url: &str,
+        config: &BackendConfig,
+        sequencer_head: Arc<AtomicU64>,
+        policy: Arc<CallPolicy>,
+    ) -> Result<Self, AnyError> {
+        let replicas = std::iter::once(url)
+            .chain(config.replicas.iter().map(String::as_str))
+            .map(|url| Replica::new(url, policy.max_timeout()))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            name: name.to_owned(),
+            replicas,
+            balance: config.balance,
+            max_lag: config.max_lag,
+            next: Default::default(),
+            sequencer_head,
+            read_breaker: CircuitBreaker::new(name, "reads", policy.breaker),
+            write_breaker: CircuitBreaker::new(name, "writes", policy.breaker),
+            policy,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The node the stateful requests go to.
+    pub fn primary(&self) -> &HttpClient<HttpBackend> {
+        &self.replicas[0].client
+    }
+
+    /// Every node of the backend, by url: the primary first.
+    pub fn nodes(&self) -> impl Iterator<Item = (&str, &HttpClient<HttpBackend>)> {
+        self.replicas
+            .iter()
+            .map(|replica| (replica.url.as_str(), &replica.client))
+    }
+
+    /// A node to read from: one of the healthy nodes keeping up with the sequencer's head,
+    /// or, if there are none, the one with the highest head.
+    pub fn client(&self) -> &HttpClient<HttpBackend> {
+        self.client_at(0)
+    }
+
+    /// A node to read from, as per [`Self::client`], that has seen block `min_head`;
+    /// the primary if none of the healthy ones has.
+    pub fn client_at(&self, min_head: u64) -> &HttpClient<HttpBackend> {
+        if self.replicas.len() == 1 {
+            return self.primary();
+        }
+
+        let sequencer_head = self.sequencer_head.load(Ordering::Relaxed);
+        let eligible = self
+            .replicas
+            .iter()
+            .filter(|replica| {
+                let head = replica.head.load(Ordering::Relaxed);
+                replica.healthy.load(Ordering::Relaxed)
+                    && head + self.max_lag >= sequencer_head
+                    && head >= min_head
+            })
+            .collect::<Vec<_>>();
+
+        let replica = if eligible.is_empty() && min_head > 0 {
+            &self.replicas[0]
+        } else if eligible.is_empty() {
+            self.replicas
+                .iter()
+                .max_by_key(|replica| replica.head.load(Ordering::Relaxed))
+                .expect("there is at least the primary")
+        } else {
+            match self.balance {
+                Balance::RoundRobin => {
+                    eligible[self.next.fetch_add(1, Ordering::Relaxed) % eligible.len()]
+                }
+                Balance::LeastLatency => eligible
+                    .into_iter()
+                    .min_by_key(|replica| replica.latency_micros.load(Ordering::Relaxed))
+                    .expect("not empty"),
+            }
+        };
+        &replica.client
+    }
+
+    /// Sends the read `request` for `method` to a node to read from, retrying it as per the
+    /// [`CallPolicy`]; the errors the backend did not answer with are told apart as per
+    /// [`ProxyError`].
+    pub async fn call<'a, T, F, R>(&'a self, method: &str, request: F) -> RpcResult<T>
+    where
+        F: Fn(&'a HttpClient<HttpBackend>) -> R,
+        R: Future<Output = Result<T, ClientError>>,
+    {
+        self.call_at(method, 0, request).await
+    }
+
+    /// Sends the read `request` for `method` as per [`Self::call`], to the nodes that have seen
+    /// block `min_head`.
+    pub async fn call_at<'a, T, F, R>(
+        &'a self,
+        method: &str,
+        min_head: u64,
+        request: F,
+    ) -> RpcResult<T>
+    where
+        F: Fn(&'a HttpClient<HttpBackend>) -> R,
+        R: Future<Output = Result<T, ClientError>>,
+    {
+        let mut retries = 0;
+        loop {
+            self.admit(RequestKind::Read)?;
+            let result = self
+                .attempt(method, RequestKind::Read, request(self.client_at(min_head)))
+                .await;
+            match result {
+                Err(error) if is_transient(&error) && retries < self.policy.retries => {
+                    retries += 1;
+                    tracing::debug!("retrying {} on {:?}: {}", method, self.name, error);
+                    metrics::counter!("sequencer_backend_retries_total", "backend" => self.name.clone())
+                        .increment(1);
+                    tokio::time::sleep(self.policy.backoff(retries)).await;
+                }
+                result => return result.map_err(|error| self.error(error)),
+            }
+        }
+    }
+
+    /// Sends `request` for `method` to the primary node, once: it may not be idempotent.
+    pub async fn call_primary<'a, T, F, R>(&'a self, method: &str, request: F) -> RpcResult<T>
+    where
+        F: FnOnce(&'a HttpClient<HttpBackend>) -> R,
+        R: Future<Output = Result<T, ClientError>>,
+    {
+        self.admit(RequestKind::Write)?;
+        self.attempt(method, RequestKind::Write, request(self.primary()))
+            .await
+            .map_err(|error| self.error(error))
+    }
+
+    fn breaker(&self, kind: RequestKind) -> &CircuitBreaker {
+        match kind {
+            RequestKind::Read => &self.read_breaker,
+            RequestKind::Write => &self.write_breaker,
+        }
+    }
+
+    /// Fails fast while the circuit of the `kind` of requests is open.
+    fn admit(&self, kind: RequestKind) -> RpcResult<()> {
+        self.breaker(kind).admit().map_err(|retry_after| {
+            ProxyError::BackendUnavailable {
+                backend: self.name.clone(),
+                reason: "failing fast while the backend is unhealthy".to_owned(),
+                retry_after: Some(retry_after),
+            }
+            .into()
+        })
+    }
+
+    async fn attempt<T>(
+        &self,
+        method: &str,
+        kind: RequestKind,
+        request: impl Future<Output = Result<T, ClientError>>,
+    ) -> Result<T, ClientError> {
+        let result = tokio::time::timeout(self.policy.timeout(method), request)
+            .await
+            .unwrap_or(Err(ClientError::RequestTimeout));
+        // The backend is healthy as long as it answers, be it with an error or by refusing a
+        // request too large; one that hangs is not, even if a slow read may be a client's doing.
+        self.breaker(kind).record(match &result {
+            Ok(_) | Err(ClientError::Call(_)) => true,
+            Err(error) => is_request_too_large(error),
+        });
+        result
+    }
+
+    pub fn error(&self, error: ClientError) -> jsonrpsee::types::ErrorObjectOwned {
+        ProxyError::from_client_error(&self.name, error)
+    }
+
+    /// The highest head among the healthy nodes.
+    pub fn head(&self) -> Option<u64> {
+        self.replicas
+            .iter()
+            .filter(|replica| replica.healthy.load(Ordering::Relaxed))
+            .map(|replica| replica.head.load(Ordering::Relaxed))
+            .max()
+    }
+
+    async fn poll(&self) {
+        futures::future::join_all(self.replicas.iter().map(|replica| replica.poll(&self.name)))
+            .await;
+    }
+}
+
+impl<'a> Route<'a> {
+    /// The same route, only to the nodes that have seen block `head`.
+    pub fn at_least(self, head: u64) -> Self {
+        Self {
+            min_head: self.min_head.max(head),
+            ..self
+        }
+    }
+
+    /// See [`Backend::client_at`].
+    pub fn client(&self) -> &'a HttpClient<HttpBackend> {
+        self.backend.client_at(self.min_head)
+    }
+
+    /// See [`Backend::call_at`].
+    pub async fn call<T, F, R>(&self, request: F) -> RpcResult<T>
+    where
+        F: Fn(&'a HttpClient<HttpBackend>) -> R,
+        R: Future<Output = Result<T, ClientError>>,
+    {
+        self.backend
+            .call_at(self.method, self.min_head, request)
+            .await
+    }
+
+    /// See [`Backend::call_primary`].
+    pub async fn call_primary<T, F, R>(&self, request: F) -> RpcResult<T>
+    where
+        F: FnOnce(&'a HttpClient<HttpBackend>) -> R,
+        R: Future<Output = Result<T, ClientError>>,
+    {
+        self.backend.call_primary(self.method, request).await
+    }
+}
+
+impl Replica {
+    fn new(url: &str, timeout: Duration) -> Result<Self, AnyError> {
+        Ok(Self {
+            url: url.to_owned(),
+            client: HttpClient::<HttpBackend>::builder()
+                .request_timeout(timeout)
+                .build(url)?,
+            healthy: AtomicBool::new(true),
+            head: Default::default(),
+            latency_micros: Default::default(),
+        })
+    }
+
+    async fn poll(&self, backend: &str) {
+        let started_at = Instant::now();
+        let head = match self.client.block_number().await {
+            Ok(head) => head.saturating_to::<u64>(),
+            Err(reason) => {
+                if self.healthy.swap(false, Ordering::Relaxed) {
+                    tracing::warn!("replica {} of {:?} is down: {}", self.url, backend, reason);
+                }
+                return;
+            }
+        };
+        let latency = started_at.elapsed().as_micros() as f64;
+
+        if !self.healthy.swap(true, Ordering::Relaxed) {
+            tracing::info!("replica {} of {:?} is up", self.url, backend);
+        }
+        self.head.store(head, Ordering::Relaxed);
+        let average = self.latency_micros.load(Ordering::Relaxed) as f64;
+        let average = if average == 0.0 {
+            latency
+        } else {
+            average + (latency - average) * LATENCY_EWMA_WEIGHT
+        };
+        self.latency_micros.store(average as u64, Ordering::Relaxed);
+
+        metrics::gauge!(
+            "sequencer_replica_head",
+            "backend" => backend.to_owned(),
+            "replica" => self.url.clone()
+        )
+        .set(head as f64);
+        metrics::gauge!(
+            "sequencer_replica_latency_seconds",
+            "backend" => backend.to_owned(),
+            "replica" => self.url.clone()
+        )
+        .set(average / 1e6);
+    }
+}
+
+impl Router {
+    /// Reads the routing-file at `path`, if any; without one everything goes to `default_url`.
+    pub fn load(
+        default_url: &str,
+        path: Option<&Path>,
+        policy: CallPolicy,
+    ) -> Result<Self, AnyError> {
+        let config = match path {
+            Some(path) => serde_json::from_slice(&std::fs::read(path)?)
+                .map_err(|reason| format!("invalid routing-file {:?}: {}", path, reason))?,
+            None => Default::default(),
+        };
+        Self::new(default_url, config, policy)
+    }
+
+    pub fn new(
+        default_url: &str,
+        mut config: RoutingConfig,
+        mut policy: CallPolicy,
+    ) -> Result<Self, AnyError> {
+        let sequencer_head = Arc::new(AtomicU64::new(0));
+        policy.timeouts.extend(
+            config
+                .timeouts_ms
+                .iter()
+                .map(|(method, timeout)| (method.clone(), Duration::from_millis(*timeout))),
+        );
+        let policy = Arc::new(policy);
+
+        let default = config
+            .backends
+            .remove(DEFAULT_BACKEND)
+            .unwrap_or_else(|| BackendConfig {
+                max_lag: default_max_lag(),
+                ..Default::default()
+            });
+        if default.url.is_some() {
+            return Err(format!(
+                "the url of backend {:?} is given by BACKEND_ETH_API_URL",
+                DEFAULT_BACKEND
+            )
+            .into());
+        }
+        let mut backends = BTreeMap::new();
+        backends.insert(
+            DEFAULT_BACKEND.to_owned(),
+            Backend::new(
+                DEFAULT_BACKEND,
+                default_url,
+                &default,
+                sequencer_head.clone(),
+                policy.clone(),
+            )?,
+        );
+        for (name, backend) in config.backends.iter() {
+            let url = backend
+                .url
+                .as_deref()
+                .ok_or_else(|| format!("backend {:?} has no url", name))?;
+            backends.insert(
+                name.clone(),
+                Backend::new(name, url, backend, sequencer_head.clone(), policy.clone())?,
+            );
+        }
+
+        let referred = config
+            .routes
+            .iter()
+            .map(|(method, name)| (method.as_str(), name))
+            .chain(
+                config
+                    .historical
+                    .iter()
+                    .map(|historical| ("historical", &historical.backend)),
+            );
+        for (route, name) in referred {
+            if !backends.contains_key(name) {
+                return Err(
+                    format!("route {:?} refers to unknown backend {:?}", route, name).into(),
+                );
+            }
+        }
+
+        Ok(Self {
+            backends,
+            routes: config.routes,
+            historical: config.historical,
+            sequencer_head,
+        })
+    }
+
+    pub fn default_backend(&self) -> &Backend {
+        &self.backends[DEFAULT_BACKEND]
+    }
+
+    pub fn backends(&self) -> impl Iterator<Item = &Backend> {
+        self.backends.values()
+    }
+
+    /// Polls the head of every node; returns the head of the `default` backend.
+    pub async fn poll(&self) -> Option<u64> {
+        futures::future::join_all(self.backends.values().map(|backend| backend.poll())).await;
+
+        self.default_backend().head()
+    }
+
+    /// Sets the sequencer's head, which the replicas' lag is measured against.
+    pub fn set_head(&self, head: u64) {
+        self.sequencer_head.store(head, Ordering::Relaxed);
+    }
+
+    /// The backend for `method` referring to `block`, given the current `head`.
+    pub fn route<'a>(&'a self, method: &'a str, block: Option<BlockId>, head: u64) -> Route<'a> {
+        let historical = self
+            .historical
+            .as_ref()
+            .filter(|historical| {
+                block.is_some_and(|block| is_historical(block, head, historical.depth))
+            })
+            .map(|historical| historical.backend.as_str());
+        let namespace = method.split_once('_').map(|(namespace, _)| namespace);
+        let name = historical
+            .or_else(|| self.routes.get(method).map(String::as_str))
+            .or_else(|| {
+                namespace
+                    .and_then(|namespace| self.routes.get(namespace))
+                    .map(String::as_str)
+            })
+            .unwrap_or(DEFAULT_BACKEND);
+
+        let backend = &self.backends[name];
+        metrics::counter!("sequencer_backend_requests_total", "backend" => backend.name.clone())
+            .increment(1);
+        Route {
+            backend,
+            method,
+            min_head: 0,
+        }
+    }
+}
+
+/// Whether `error` is for want of an answer, so that the request may be retried. A request that
+/// timed out is not: retrying it would only add to the load of a slow backend.
+fn is_transient(error: &ClientError) -> bool {
+    matches!(
+        error,
+        ClientError::Transport(_) | ClientError::RestartNeeded(_)
+    )
+}
+
+/// Whether `block` is more than `depth` blocks behind the `head`; a hash is not known to be.
+fn is_historical(block: BlockId, head: u64, depth: u64) -> bool {
+    match block {
+        BlockId::Hash(_) => false,
+        BlockId::Number(BlockNumberOrTag::Earliest) => true,
+        BlockId::Number(BlockNumberOrTag::Number(number)) => number.saturating_add(depth) < head,
+        BlockId::Number(_) => false,
+    }
+}
+
+fn default_historical_depth() -> u64 {
+    128
+}
+
+fn default_max_lag() -> u64 {
+    5
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+
+    use crate::error::{BACKEND_TIMEOUT_CODE, BACKEND_UNAVAILABLE_CODE};
+
+    use super::*;
+
+    #[test]
+    fn a_hung_backend_opens_the_read_breaker() {
+        // Accepts the connections, and never answers on them.
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            let mut connections = vec![];
+            for connection in listener.incoming() {
+                connections.push(connection);
+            }
+        });
+
+        let policy = CallPolicy {
+            timeout: Duration::from_millis(50),
+            breaker: BreakerConfig {
+                failure_threshold: 2,
+                cooldown: Duration::from_secs(60),
+            },
+            ..Default::default()
+        };
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let router = Router::new(&url, Default::default(), policy).unwrap();
+            let backend = router.default_backend();
+            for _ in 0..2 {
+                let error = backend
+                    .call("eth_blockNumber", |client| client.block_number())
+                    .await
+                    .unwrap_err();
+                assert_eq!(error.code(), BACKEND_TIMEOUT_CODE);
+            }
+
+            let started_at = Instant::now();
+            let error = backend
+                .call("eth_blockNumber", |client| client.block_number())
+                .await
+                .unwrap_err();
+            assert_eq!(error.code(), BACKEND_UNAVAILABLE_CODE);
+            assert!(started_at.elapsed() < Duration::from_millis(50));
+        });
+    }
+}
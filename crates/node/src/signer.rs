@@ -0,0 +1,116 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use alloy_dyn_abi::TypedData;
+use alloy_primitives::{eip191_hash_message, keccak256, Address, Bytes, B256, U256};
+use reth_primitives::{Signature, Transaction, TransactionSigned};
+use secp256k1::{Message, SecretKey, SECP256K1};
+
+use crate::AnyError;
+
+/// Signs with the keys of encrypted JSON keystores (as written by geth's `account new`).
+#[derive(Debug)]
+pub struct Signer {
+    keys: BTreeMap<Address, SecretKey>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SignerError {
+    #[error("unknown account {0}")]
+    UnknownAccount(Address),
+    #[error("invalid typed data: {0}")]
+    InvalidTypedData(String),
+    #[error("failed to sign: {0}")]
+    Signing(#[from] secp256k1::Error),
+}
+
+impl Signer {
+    /// Decrypts every keystore in `dir` with `password`.
+    pub fn load(dir: &Path, password: &str) -> Result<Self, AnyError> {
+        let mut keys = BTreeMap::new();
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if !path.is_file() {
+                continue;
+            }
+            let secret = eth_keystore::decrypt_key(&path, password)
+                .map_err(|reason| format!("failed to decrypt keystore {:?}: {}", path, reason))?;
+            let key = SecretKey::from_slice(&secret)?;
+            let address = address_of(&key);
+            tracing::info!("loaded account {} from {:?}", address, path);
+            keys.insert(address, key);
+        }
+        Ok(Self { keys })
+    }
+
+    pub fn accounts(&self) -> Vec<Address> {
+        self.keys.keys().copied().collect()
+    }
+
+    pub fn has_account(&self, address: &Address) -> bool {
+        self.keys.contains_key(address)
+    }
+
+    /// Signs `transaction` as `address`.
+    pub fn sign_transaction(
+        &self,
+        address: Address,
+        transaction: Transaction,
+    ) -> Result<TransactionSigned, SignerError> {
+        let signature = self.sign_hash(address, transaction.signature_hash())?;
+        Ok(TransactionSigned::from_transaction_and_signature(
+            transaction,
+            signature,
+        ))
+    }
+
+    /// Signs `message` as per EIP-191 (`personal_sign`, `eth_sign`).
+    pub fn sign_message(&self, address: Address, message: &[u8]) -> Result<Bytes, SignerError> {
+        let signature = self.sign_hash(address, eip191_hash_message(message))?;
+        Ok(to_rsv(&signature))
+    }
+
+    /// Signs the EIP-712 typed data `data` (`eth_signTypedData_v4`).
+    pub fn sign_typed_data(
+        &self,
+        address: Address,
+        data: serde_json::Value,
+    ) -> Result<Bytes, SignerError> {
+        let data: TypedData = serde_json::from_value(data)
+            .map_err(|reason| SignerError::InvalidTypedData(reason.to_string()))?;
+        let hash = data
+            .eip712_signing_hash()
+            .map_err(|reason| SignerError::InvalidTypedData(reason.to_string()))?;
+        let signature = self.sign_hash(address, hash)?;
+        Ok(to_rsv(&signature))
+    }
+
+    fn sign_hash(&self, address: Address, hash: B256) -> Result<Signature, SignerError> {
+        let key = self
+            .keys
+            .get(&address)
+            .ok_or(SignerError::UnknownAccount(address))?;
+        let (recovery_id, signature) = SECP256K1
+            .sign_ecdsa_recoverable(&Message::from_digest_slice(hash.as_slice())?, key)
+            .serialize_compact();
+        Ok(Signature {
+            r: U256::from_be_slice(&signature[..32]),
+            s: U256::from_be_slice(&signature[32..]),
+            odd_y_parity: recovery_id.to_i32() != 0,
+        })
+    }
+}
+
+fn address_of(key: &SecretKey) -> Address {
+    let public_key = key.public_key(SECP256K1).serialize_uncompressed();
+    Address::from_slice(&keccak256(&public_key[1..])[12..])
+}
+
+/// The signature as 65 bytes: `r`, `s` and `v` being 27 or 28.
+fn to_rsv(signature: &Signature) -> Bytes {
+    let mut rsv = Vec::with_capacity(65);
+    rsv.extend_from_slice(&signature.r.to_be_bytes::<32>());
+    rsv.extend_from_slice(&signature.s.to_be_bytes::<32>());
+    rsv.push(27 + signature.odd_y_parity as u8);
+    rsv.into()
+}
@@ -1,3 +1,6 @@
+mod check_tx;
+pub use check_tx::CheckTx;
+
 mod init;
 pub use init::Init;
 
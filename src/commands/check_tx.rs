@@ -0,0 +1,52 @@
+use std::path::PathBuf;
+
+use alloy_primitives::Bytes;
+use node::admission::rules::{Rules, Verdict};
+use node::admission::Admission;
+use reth_primitives::TransactionSigned;
+use structopt::StructOpt;
+
+use crate::{AnyError, Cli};
+
+/// Evaluates a signed transaction against the admission lists and rules.
+#[derive(Debug, StructOpt)]
+pub struct CheckTx {
+    #[structopt(long, env = "ADMISSION_LISTS_PATH")]
+    admission_lists_path: Option<PathBuf>,
+
+    #[structopt(long, env = "ADMISSION_RULES_PATH")]
+    admission_rules_path: Option<PathBuf>,
+
+    /// Hex-encoded signed transaction, as passed to `eth_sendRawTransaction`.
+    raw_tx: Bytes,
+}
+
+impl CheckTx {
+    pub async fn run(&self, _cli: &Cli) -> Result<(), AnyError> {
+        let admission = Admission::load(self.admission_lists_path.clone())?;
+        let rules = Rules::load(self.admission_rules_path.clone())?;
+
+        let transaction = TransactionSigned::decode_enveloped(&mut self.raw_tx.as_ref())?
+            .into_ecrecovered()
+            .ok_or("invalid transaction signature")?;
+
+        println!("hash:      {}", transaction.hash());
+        println!("sender:    {}", transaction.signer());
+        match transaction.to() {
+            Some(recipient) => println!("recipient: {}", recipient),
+            None => println!("recipient: <contract creation>"),
+        }
+
+        if let Err(rejection) = admission.check(transaction.signer(), transaction.to()) {
+            println!("verdict:   deny ({})", rejection);
+            return Ok(());
+        }
+        match rules.evaluate(&transaction) {
+            Verdict::Allow => println!("verdict:   allow"),
+            Verdict::Deny(rule) => println!("verdict:   deny (rule {:?})", rule),
+            Verdict::Deprioritize(rule) => println!("verdict:   deprioritize (rule {:?})", rule),
+        }
+
+        Ok(())
+    }
+}
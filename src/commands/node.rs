@@ -1,69 +1,425 @@
 
-use std::{net::SocketAddr, path::PathBuf};
+use std::{net::SocketAddr, path::PathBuf, sync::Arc};
 
+use alloy_primitives::B256;
 use humantime::Duration;
-use jsonrpsee::RpcModule;
-use node::api::EthFilterApiServer;
-use node::api::{EngineApiServer, EthApiServer};
+use jsonrpsee::{Methods, RpcModule};
+use node::admission::rules::Rules;
+use node::admission::Admission;
+use node::api::{
+    AdmissionApiServer, EngineApiServer, EthApiServer, EthFilterApiServer, EthPubSubApiServer,
+    MinerApiServer, RedstoneApiServer, ServerConfig, TxPoolApiServer,
+};
+use node::circuit_breaker::BreakerConfig;
+use node::filters::Filters;
+use node::gas_oracle::{GasOracle, GasOracleConfig, Strategy};
+use node::logs::{LogsChunking, LogsLimits};
+use node::pool::journal::Journal;
+use node::preflight::Preflight;
+use node::public_server::PublicServer;
+use node::quota::{QuotaApiServer, Quotas};
+use node::rate_limit::{Budget, RateLimiter};
+use node::response_cache::ResponseCache;
+use node::routing::{CallPolicy, Router};
+use node::signer::Signer;
 use reth_rpc::JwtSecret;
-use reth_rpc_api::EngineEthApiClient;
 use structopt::StructOpt;
+use tokio::signal::unix::{signal, SignalKind};
 
+use crate::config::{Auth, Config, TxPoolExposure};
 use crate::{AnyError, Cli};
 
+const RATE_LIMITER_EVICT_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+const FILTERS_EVICT_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Runs the sequencer's RPC-servers in front of the backends.
+///
+/// Every setting may also be given in the `--config` file, under the key its doc names; the
+/// flags and env-vars take precedence over the file, which takes precedence over the defaults.
 #[derive(Debug, StructOpt)]
 pub struct Node {
-    // #[structopt(long, env = "CHAIN_ID")]
-    // chain_id: ChainId,
-    #[structopt(long, env = "RPC_BIND_ADDR_A", default_value = "0.0.0.0:8551")]
-    rpc_bind_addr_a: SocketAddr,
+    /// TOML-file with the settings. Reloaded on SIGHUP, where the rate-limits take effect.
+    #[structopt(long, env = "CONFIG_PATH")]
+    config: Option<PathBuf>,
+
+    /// The chain the backend must serve; checked at startup. `backends.chain_id`
+    #[structopt(long, env = "CHAIN_ID")]
+    chain_id: Option<u64>,
+
+    /// The genesis hash the backend must be on; checked at startup. `backends.genesis_hash`
+    #[structopt(long, env = "GENESIS_HASH")]
+    genesis_hash: Option<B256>,
+
+    /// The Engine API methods the engine endpoint must support; checked at startup.
+    /// `backends.engine_required_methods` [default: the `V2` methods]
+    #[structopt(long, env = "ENGINE_REQUIRED_METHODS", use_delimiter = true)]
+    engine_required_methods: Option<Vec<String>>,
 
-    #[structopt(long, env = "RPC_BIND_ADDR_B", default_value = "0.0.0.0:8545")]
-    rpc_bind_addr_b: SocketAddr,
+    /// Start without checking the backends first. `backends.skip_preflight` [default: false]
+    #[structopt(long, env = "SKIP_PREFLIGHT")]
+    skip_preflight: Option<bool>,
 
+    /// `servers.a.bind_addr` [default: 0.0.0.0:8551]
+    #[structopt(long, env = "RPC_BIND_ADDR_A")]
+    rpc_bind_addr_a: Option<SocketAddr>,
+
+    /// `servers.b.bind_addr` [default: 0.0.0.0:8545]
+    #[structopt(long, env = "RPC_BIND_ADDR_B")]
+    rpc_bind_addr_b: Option<SocketAddr>,
+
+    /// `auth.engine_jwt_secret_path`
     #[structopt(long, env = "BACKEND_ENGINE_API_JWT_SECRET_PATH")]
-    engine_api_secret_path: PathBuf,
+    engine_api_secret_path: Option<PathBuf>,
 
+    /// `backends.engine_api_url`
     #[structopt(long, env = "BACKEND_ENGINE_API_URL")]
-    engine_api_url: String,
+    engine_api_url: Option<String>,
 
+    /// `backends.eth_api_url`
     #[structopt(long, env = "BACKEND_ETH_API_URL")]
-    eth_api_url: String,
+    eth_api_url: Option<String>,
+
+    /// JSON-file with additional backends and the methods routed to them.
+    /// `backends.routing_path`
+    #[structopt(long, env = "BACKEND_ROUTING_PATH")]
+    backend_routing_path: Option<PathBuf>,
+
+    /// WebSocket endpoint of the `default` backend: its `newHeads` subscription
+    /// updates the head as soon as a block lands. Without one the head is polled for.
+    /// `backends.eth_ws_url`
+    #[structopt(long, env = "BACKEND_ETH_WS_URL")]
+    eth_ws_url: Option<String>,
+
+    /// `backends.poll_interval` [default: 1s]
+    #[structopt(long, env = "BACKEND_POLL_INTERVAL")]
+    backend_poll_interval: Option<Duration>,
+
+    /// How long a backend has to answer; the routing-file may set it per method.
+    /// `backends.timeout` [default: 30s]
+    #[structopt(long, env = "BACKEND_TIMEOUT")]
+    backend_timeout: Option<Duration>,
+
+    /// The times a read that got no answer from a backend is retried.
+    /// `backends.retries` [default: 2]
+    #[structopt(long, env = "BACKEND_RETRIES")]
+    backend_retries: Option<u32>,
+
+    /// The delay before the first retry; it doubles with each retry, and is jittered.
+    /// `backends.retry_backoff` [default: 100ms]
+    #[structopt(long, env = "BACKEND_RETRY_BACKOFF")]
+    backend_retry_backoff: Option<Duration>,
+
+    /// The failures in a row after which a backend's requests fail fast. Zero disables it.
+    /// `backends.circuit_breaker_threshold` [default: 5]
+    #[structopt(long, env = "BACKEND_CIRCUIT_BREAKER_THRESHOLD")]
+    backend_circuit_breaker_threshold: Option<u32>,
+
+    /// How long a backend's requests fail fast before one is let through to probe it.
+    /// `backends.circuit_breaker_cooldown` [default: 10s]
+    #[structopt(long, env = "BACKEND_CIRCUIT_BREAKER_COOLDOWN")]
+    backend_circuit_breaker_cooldown: Option<Duration>,
+
+    /// JSON-file with the sender/recipient allow- and deny-lists. Re-read on SIGHUP.
+    /// `policies.admission_lists_path`
+    #[structopt(long, env = "ADMISSION_LISTS_PATH")]
+    admission_lists_path: Option<PathBuf>,
+
+    /// JSON-file with the transaction admission rules. Reloaded when modified.
+    /// `policies.admission_rules_path`
+    #[structopt(long, env = "ADMISSION_RULES_PATH")]
+    admission_rules_path: Option<PathBuf>,
+
+    /// `policies.admission_rules_poll_interval` [default: 5s]
+    #[structopt(long, env = "ADMISSION_RULES_POLL_INTERVAL")]
+    admission_rules_poll_interval: Option<Duration>,
+
+    /// `servers.metrics_bind_addr`
+    #[structopt(long, env = "METRICS_BIND_ADDR")]
+    metrics_bind_addr: Option<SocketAddr>,
+
+    /// Take the client-address on server [B] from the `X-Forwarded-For` header, as its last
+    /// address. `servers.b.trust_forwarded_for` [default: false]
+    #[structopt(long, env = "RPC_B_TRUST_FORWARDED_FOR")]
+    rpc_b_trust_forwarded_for: Option<bool>,
+
+    /// Take the API-key on server [B] from the URL-path too, as the segment following this
+    /// prefix (e.g. `/v1` for `https://rpc.example/v1/<api-key>`).
+    /// `servers.b.api_key_path_prefix`
+    #[structopt(long, env = "RPC_B_API_KEY_PATH_PREFIX")]
+    rpc_b_api_key_path_prefix: Option<String>,
+
+    /// Rate-limits on server [B], as `RATE[:BURST]` with `RATE` in requests per second.
+    /// `limits.per_ip.write`, and so on. Reloaded on SIGHUP.
+    #[structopt(long, env = "RPC_B_RATE_LIMIT_IP_WRITE")]
+    rpc_b_rate_limit_ip_write: Option<Budget>,
+
+    #[structopt(long, env = "RPC_B_RATE_LIMIT_IP_EXPENSIVE")]
+    rpc_b_rate_limit_ip_expensive: Option<Budget>,
+
+    #[structopt(long, env = "RPC_B_RATE_LIMIT_IP_OTHER")]
+    rpc_b_rate_limit_ip_other: Option<Budget>,
+
+    /// `limits.per_api_key.write`, and so on. Reloaded on SIGHUP.
+    #[structopt(long, env = "RPC_B_RATE_LIMIT_API_KEY_WRITE")]
+    rpc_b_rate_limit_api_key_write: Option<Budget>,
+
+    #[structopt(long, env = "RPC_B_RATE_LIMIT_API_KEY_EXPENSIVE")]
+    rpc_b_rate_limit_api_key_expensive: Option<Budget>,
+
+    #[structopt(long, env = "RPC_B_RATE_LIMIT_API_KEY_OTHER")]
+    rpc_b_rate_limit_api_key_other: Option<Budget>,
+
+    /// File where the accepted transactions are journaled to survive restarts.
+    /// `policies.pool_journal_path`
+    #[structopt(long, env = "POOL_JOURNAL_PATH")]
+    pool_journal_path: Option<PathBuf>,
+
+    /// How often the included transactions are removed from the pool and its journal.
+    /// `policies.pool_maintenance_interval` [default: 10s]
+    #[structopt(long, env = "POOL_MAINTENANCE_INTERVAL")]
+    pool_maintenance_interval: Option<Duration>,
+
+    /// Transactions not included within this time are dropped from the pool.
+    /// `policies.pool_max_age` [default: 3h]
+    #[structopt(long, env = "POOL_MAX_AGE")]
+    pool_max_age: Option<Duration>,
 
-    #[structopt(long, env = "BACKEND_POLL_INTERVAL", default_value = "1s")]
-    backend_poll_interval: Duration,
+    /// Which of the `txpool_*` methods server [B] serves: `full`, `status` or `none`.
+    /// `servers.b.txpool` [default: status]
+    #[structopt(long, env = "RPC_B_TXPOOL")]
+    rpc_b_txpool: Option<TxPoolExposure>,
+
+    /// JSON-file with the API-keys, their tiers and the compute-unit costs of the methods.
+    /// Re-read on SIGHUP. `auth.api_keys_path`
+    #[structopt(long, env = "RPC_B_API_KEYS_PATH")]
+    rpc_b_api_keys_path: Option<PathBuf>,
+
+    /// The number of recent blocks the gas-price suggestions are based on.
+    /// `policies.gas_oracle_window` [default: 20]
+    #[structopt(long, env = "GAS_ORACLE_WINDOW")]
+    gas_oracle_window: Option<usize>,
+
+    /// How the suggested tip is derived from the recent tips: `max`, `median` or `percentile:N`.
+    /// `policies.gas_oracle_strategy` [default: percentile:60]
+    #[structopt(long, env = "GAS_ORACLE_STRATEGY")]
+    gas_oracle_strategy: Option<Strategy>,
+
+    /// The lowest tip ever suggested, in wei. `policies.gas_oracle_floor` [default: 0]
+    #[structopt(long, env = "GAS_ORACLE_FLOOR")]
+    gas_oracle_floor: Option<u128>,
+
+    /// The number of immutable results (blocks, mined transactions, receipts) kept in memory.
+    /// Zero disables the cache. `limits.response_cache_size` [default: 10000]
+    #[structopt(long, env = "RESPONSE_CACHE_SIZE")]
+    response_cache_size: Option<usize>,
+
+    /// Filters not polled for this long are uninstalled. `limits.filter_ttl` [default: 5m]
+    #[structopt(long, env = "FILTER_TTL")]
+    filter_ttl: Option<Duration>,
+
+    /// The most filters installed at once. `limits.max_filters` [default: 10000]
+    #[structopt(long, env = "MAX_FILTERS")]
+    max_filters: Option<usize>,
+
+    /// The most filters installed at once by the same client of server [B], by IP-address or
+    /// API-key. `limits.max_filters_per_client` [default: 100]
+    #[structopt(long, env = "MAX_FILTERS_PER_CLIENT")]
+    max_filters_per_client: Option<usize>,
+
+    /// The most blocks an `eth_getLogs` query on server [B] may span.
+    /// `servers.b.logs_max_block_range`
+    #[structopt(long, env = "RPC_B_LOGS_MAX_BLOCK_RANGE")]
+    rpc_b_logs_max_block_range: Option<u64>,
+
+    /// The most logs an `eth_getLogs` query on server [B] may return.
+    /// `servers.b.logs_max_results`
+    #[structopt(long, env = "RPC_B_LOGS_MAX_RESULTS")]
+    rpc_b_logs_max_results: Option<usize>,
+
+    /// `eth_getLogs` queries spanning more blocks are fetched from the backend in chunks
+    /// of this many blocks; the chunks below the finalized block are cached.
+    /// `limits.logs_chunk_size`
+    #[structopt(long, env = "LOGS_CHUNK_SIZE")]
+    logs_chunk_size: Option<u64>,
+
+    /// The most chunks of an `eth_getLogs` query fetched at once.
+    /// `limits.logs_chunk_parallelism` [default: 4]
+    #[structopt(long, env = "LOGS_CHUNK_PARALLELISM")]
+    logs_chunk_parallelism: Option<usize>,
+
+    /// Directory of encrypted JSON keystores to sign `eth_sendTransaction` and `eth_sign*` with.
+    /// Meant for development and internal tooling. `auth.signer_keystore_dir`
+    #[structopt(long, env = "SIGNER_KEYSTORE_DIR")]
+    signer_keystore_dir: Option<PathBuf>,
+
+    /// File holding the password of the keystores. `auth.signer_password_path`
+    #[structopt(long, env = "SIGNER_PASSWORD_PATH")]
+    signer_password_path: Option<PathBuf>,
+
+    /// Sign with the keystores on server [B] too, not only on server [A].
+    /// `servers.b.signer` [default: false]
+    #[structopt(long, env = "RPC_B_SIGNER")]
+    rpc_b_signer: Option<bool>,
+
+    /// Forward `latest` on server [B] as the block `eth_blockNumber` reports, by hash, so that
+    /// the reads of a client agree with the head it has been told.
+    /// `servers.b.pin_latest` [default: false]
+    #[structopt(long, env = "RPC_B_PIN_LATEST")]
+    rpc_b_pin_latest: Option<bool>,
 }
 
 impl Node {
     pub async fn run(&self, _cli: &Cli) -> Result<(), AnyError> {
-        let jwt_secret = JwtSecret::from_file(self.engine_api_secret_path.as_ref())?;
-        let api = node::api::Api::new(&self.eth_api_url, &self.engine_api_url, jwt_secret).await?;
+        let config = &self.config()?;
+
+        if let Some(metrics_bind_addr) = config.servers.metrics_bind_addr {
+            tracing::info!("Binding {} for metrics", metrics_bind_addr);
+            metrics_exporter_prometheus::PrometheusBuilder::new()
+                .with_http_listener(metrics_bind_addr)
+                .install()?;
+        }
+
+        let required = "required by Config::validate";
+        let jwt_secret_path = config
+            .auth
+            .engine_jwt_secret_path
+            .as_deref()
+            .expect(required);
+        let eth_api_url = config.backends.eth_api_url.as_deref().expect(required);
+        let engine_api_url = config.backends.engine_api_url.as_deref().expect(required);
+
+        let jwt_secret = JwtSecret::from_file(jwt_secret_path)?;
+        let admission = match config.policies.admission_lists.clone() {
+            Some(lists) => Admission::new(lists),
+            None => Admission::load(config.policies.admission_lists_path.clone())?,
+        };
+        let rules = Rules::load(config.policies.admission_rules_path.clone())?;
+        let journal = config
+            .policies
+            .pool_journal_path
+            .clone()
+            .map(Journal::open)
+            .transpose()?;
+        let call_policy = CallPolicy {
+            timeout: config.backends.timeout,
+            timeouts: Default::default(),
+            retries: config.backends.retries,
+            retry_backoff: config.backends.retry_backoff,
+            breaker: BreakerConfig {
+                failure_threshold: config.backends.circuit_breaker_threshold,
+                cooldown: config.backends.circuit_breaker_cooldown,
+            },
+        };
+        let router = match config.backends.routing.clone() {
+            Some(routing) => Router::new(eth_api_url, routing, call_policy)?,
+            None => Router::load(
+                eth_api_url,
+                config.backends.routing_path.as_deref(),
+                call_policy,
+            )?,
+        };
+        let api = node::api::Api::new(
+            router,
+            engine_api_url,
+            jwt_secret,
+            admission,
+            rules,
+            journal,
+            GasOracle::new(GasOracleConfig {
+                window: config.policies.gas_oracle_window,
+                strategy: config.policies.gas_oracle_strategy,
+                floor: config.policies.gas_oracle_floor,
+            }),
+            ResponseCache::new(config.limits.response_cache_size),
+            Filters::new(
+                config.limits.filter_ttl,
+                config.limits.max_filters,
+                config.limits.max_filters_per_client,
+            ),
+            LogsChunking {
+                chunk_size: config.limits.logs_chunk_size,
+                parallelism: config.limits.logs_chunk_parallelism,
+            },
+        )
+        .await?;
+
+        if config.backends.skip_preflight {
+            tracing::warn!("Skipping the preflight checks");
+        } else {
+            api.preflight(&Preflight {
+                chain_id: config.backends.chain_id,
+                genesis_hash: config.backends.genesis_hash,
+                engine_methods: config.backends.engine_required_methods.clone(),
+            })
+            .await?;
+        }
+
+        let restored = api.restore_pool().await?;
+        tracing::info!("Restored {} transactions from the pool-journal", restored);
+
+        let quotas = match config.auth.api_keys.clone() {
+            Some(api_keys) => Quotas::new(api_keys)?,
+            None => Quotas::load(config.auth.api_keys_path.clone())?,
+        };
+        let signer = load_signer(&config.auth)?.map(Arc::new);
+        let api_a = api.for_server(ServerConfig {
+            signer: signer.clone(),
+            ..Default::default()
+        });
 
         let mut rpc_module_a = RpcModule::new(());
         let mut rpc_module_b = RpcModule::new(());
 
-        rpc_module_a.merge(EthApiServer::into_rpc(api.clone()))?;
-        rpc_module_a.merge(EngineApiServer::into_rpc(api.clone()))?;
-        rpc_module_a.merge(EthFilterApiServer::into_rpc(api.clone()))?;
+        rpc_module_a.merge(EthApiServer::into_rpc(api_a.clone()))?;
+        rpc_module_a.merge(EngineApiServer::into_rpc(api_a.clone()))?;
+        rpc_module_a.merge(EthFilterApiServer::into_rpc(api_a.clone()))?;
+        rpc_module_a.merge(AdmissionApiServer::into_rpc(api_a.clone()))?;
+        rpc_module_a.merge(TxPoolApiServer::into_rpc(api_a.clone()))?;
+        rpc_module_a.merge(RedstoneApiServer::into_rpc(api_a.clone()))?;
+        rpc_module_a.merge(MinerApiServer::into_rpc(api_a.clone()))?;
+        rpc_module_a.merge(QuotaApiServer::into_rpc(quotas.clone()))?;
 
-        rpc_module_b.merge(EthApiServer::into_rpc(api.clone()))?;
-        rpc_module_b.merge(EthFilterApiServer::into_rpc(api.clone()))?;
+        let api_b = api.for_server(ServerConfig {
+            logs_limits: LogsLimits {
+                max_block_range: config.servers.b.logs_max_block_range,
+                max_results: config.servers.b.logs_max_results,
+            },
+            signer: signer.filter(|_| config.servers.b.signer),
+            pin_latest: config.servers.b.pin_latest,
+        });
+        rpc_module_b.merge(EthApiServer::into_rpc(api_b.clone()))?;
+        rpc_module_b.merge(EthFilterApiServer::into_rpc(api_b.clone()))?;
+        rpc_module_b.merge(EthPubSubApiServer::into_rpc(api_b.clone()))?;
+        rpc_module_b.merge(RedstoneApiServer::into_rpc(api_b.clone()))?;
+        rpc_module_b.merge(select_methods(
+            TxPoolApiServer::into_rpc(api_b.clone()),
+            |name| match config.servers.b.txpool {
+                TxPoolExposure::Full => true,
+                TxPoolExposure::Status => name == "txpool_status",
+                TxPoolExposure::None => false,
+            },
+        )?)?;
 
-        tracing::info!("Binding {} for RPC server [A]", self.rpc_bind_addr_a);
+        tracing::info!("Binding {} for RPC server [A]", config.servers.a.bind_addr);
         let rpc_server_a = jsonrpsee::server::ServerBuilder::new()
-            .build(self.rpc_bind_addr_a)
+            .build(config.servers.a.bind_addr)
             .await?;
 
-        tracing::info!("Binding {} for RPC server [B]", self.rpc_bind_addr_b);
-        let rpc_server_b = jsonrpsee::server::ServerBuilder::new()
-            .build(self.rpc_bind_addr_b)
-            .await?;
+        // Attached even without budgets or API-keys, which a reload may set.
+        let rate_limiter = Arc::new(RateLimiter::new(config.limits.rate_limit_config()));
+        let rpc_server_b = PublicServer::new(config.servers.b.bind_addr)
+            .with_trust_forwarded_for(config.servers.b.trust_forwarded_for)
+            .with_api_key_path_prefix(config.servers.b.api_key_path_prefix.clone())
+            .with_rate_limiter(rate_limiter.clone())
+            .with_quotas(quotas.clone());
+        let mut hangups = signal(SignalKind::hangup())?;
 
         tracing::info!("Starting RPC-server [A]");
         let rpc_running_a = rpc_server_a.start(rpc_module_a);
 
+        tracing::info!("Binding {} for RPC server [B]", config.servers.b.bind_addr);
         tracing::info!("Starting RPC-server [B]");
-        let rpc_running_b = rpc_server_b.start(rpc_module_b);
+        let rpc_running_b = rpc_server_b.start(rpc_module_b)?;
 
         let rpc_stopped_a = async move {
             rpc_running_a.stopped().await;
@@ -73,19 +429,155 @@ impl Node {
             rpc_running_b.stopped().await;
             tracing::info!("RPC-server [B] stopped.");
         };
-        let block_num_being_updated = async move {
-            let mut ticks = tokio::time::interval(*self.backend_poll_interval);
-            
+        let rules_api = api.clone();
+        let rules_being_reloaded = async move {
+            let mut ticks = tokio::time::interval(config.policies.admission_rules_poll_interval);
+
             loop {
                 let _ = ticks.tick().await;
-                let block_number = match api.backend_eth_api().block_number().await {
-                    Ok(block_number) => block_number,
+                if let Err(reason) = rules_api.rules().reload_if_changed() {
+                    tracing::warn!("failed to reload admission rules: {}", reason);
+                }
+            }
+        };
+        let reload_api = api.clone();
+        let reload_rate_limiter = rate_limiter.clone();
+        let config_being_reloaded = async move {
+            let mut applied = config.clone();
+
+            loop {
+                let _ = hangups.recv().await;
+                tracing::info!("SIGHUP: reloading the configuration");
+                let reloaded = match self.config() {
+                    Ok(reloaded) => reloaded,
                     Err(reason) => {
-                        tracing::warn!("failed to fetch block-number: {}", reason);
+                        tracing::warn!("failed to reload the configuration: {}", reason);
                         continue;
                     }
                 };
-                api.set_current_block_number(block_number);
+                for section in applied.restart_required(&reloaded) {
+                    tracing::warn!("the changes to `{}` take effect on restart", section);
+                }
+                reload_rate_limiter.reconfigure(reloaded.limits.rate_limit_config());
+                applied.limits.per_ip = reloaded.limits.per_ip;
+                applied.limits.per_api_key = reloaded.limits.per_api_key;
+
+                match reloaded.policies.admission_lists {
+                    Some(lists) => reload_api.admission().replace(lists),
+                    None => {
+                        if let Err(reason) = reload_api.admission().reload() {
+                            tracing::warn!("failed to reload the admission lists: {}", reason);
+                        }
+                    }
+                }
+                if let Err(reason) = reload_api.rules().reload_if_changed() {
+                    tracing::warn!("failed to reload admission rules: {}", reason);
+                }
+                match reloaded.auth.api_keys {
+                    Some(api_keys) => {
+                        if let Err(reason) = quotas.reconfigure(api_keys) {
+                            tracing::warn!("failed to reconfigure the api-keys: {}", reason);
+                        }
+                    }
+                    None => {
+                        if let Err(reason) = quotas.reload() {
+                            tracing::warn!("failed to reload the api-keys: {}", reason);
+                        }
+                    }
+                }
+            }
+        };
+        let rate_limiter_being_evicted = async move {
+            let mut ticks = tokio::time::interval(RATE_LIMITER_EVICT_INTERVAL);
+
+            loop {
+                let _ = ticks.tick().await;
+                rate_limiter.evict_idle();
+            }
+        };
+        let filters_api = api.clone();
+        let filters_being_evicted = async move {
+            let mut ticks = tokio::time::interval(FILTERS_EVICT_INTERVAL);
+
+            loop {
+                let _ = ticks.tick().await;
+                filters_api.filters().evict_idle();
+            }
+        };
+        let pool_api = api.clone();
+        let pool_being_maintained = async move {
+            let mut ticks = tokio::time::interval(config.policies.pool_maintenance_interval);
+
+            loop {
+                let _ = ticks.tick().await;
+                if let Err(reason) = pool_api.prune_pool(config.policies.pool_max_age).await {
+                    tracing::warn!("failed to prune the pool: {}", reason);
+                    continue;
+                }
+                if let Err(reason) = pool_api.compact_pool_journal() {
+                    tracing::warn!("failed to compact the pool-journal: {}", reason);
+                }
+            }
+        };
+        let deprioritized_api = api.clone();
+        let deprioritized_being_forwarded = async move {
+            deprioritized_api.forward_deprioritized().await;
+        };
+        let logs_api = api.clone();
+        let logs_being_followed = async move {
+            logs_api.follow_logs().await;
+        };
+        let heads_api = api.clone();
+        let new_heads_being_followed = async move {
+            match config.backends.eth_ws_url.as_deref() {
+                Some(eth_ws_url) => heads_api.follow_new_heads(eth_ws_url).await,
+                None => std::future::pending().await,
+            }
+        };
+        let block_num_being_updated = async move {
+            let mut ticks = tokio::time::interval(config.backends.poll_interval);
+            let mut new_heads = api.heads().subscribe();
+            let mut last_observed: Option<u64> = None;
+
+            loop {
+                tokio::select! {
+                    _ = ticks.tick() => {
+                        let Some(polled) = api.poll_backends().await else {
+                            tracing::warn!("failed to fetch block-number: no backend node is up");
+                            continue;
+                        };
+                        // Polling stands in for the subscription while there is none,
+                        // and catches up with the heads it may have missed.
+                        let tracked = api.heads().head_number();
+                        if !api.heads().is_pushed() || !tracked.is_some_and(|t| t >= polled) {
+                            if let Err(reason) = api.fetch_head(polled).await {
+                                tracing::warn!("failed to fetch header #{}: {}", polled, reason);
+                            }
+                        }
+                        if let Err(reason) = api.refresh_finalized().await {
+                            tracing::warn!("failed to fetch the finalized block: {}", reason);
+                        }
+                    }
+                    _ = new_heads.recv() => {}
+                }
+                let Some(head) = api.heads().head_number() else {
+                    continue;
+                };
+
+                let window = api.gas_oracle().window() as u64;
+                let first = match last_observed {
+                    None => head.saturating_sub(window - 1),
+                    Some(last) if last < head => (last + 1).max(head.saturating_sub(window - 1)),
+                    Some(last) if last > head => head,
+                    Some(_) => continue,
+                };
+                for number in first..=head {
+                    if let Err(reason) = api.observe_block(number).await {
+                        tracing::warn!("failed to observe block #{}: {}", number, reason);
+                        break;
+                    }
+                    last_observed = Some(number);
+                }
             }
         };
 
@@ -93,10 +585,191 @@ impl Node {
             () = rpc_stopped_a => {},
             () = rpc_stopped_b => {},
             () = block_num_being_updated => {},
+            () = new_heads_being_followed => {},
+            () = logs_being_followed => {},
+            () = rules_being_reloaded => {},
+            () = config_being_reloaded => {},
+            () = rate_limiter_being_evicted => {},
+            () = pool_being_maintained => {},
+            () = deprioritized_being_forwarded => {},
+            () = filters_being_evicted => {},
         };
 
         tracing::info!("Bye!");
 
         Ok(())
     }
+
+    /// The settings of the `--config` file, overridden by those given as flags or env-vars.
+    fn config(&self) -> Result<Config, AnyError> {
+        let mut config = match self.config.as_deref() {
+            Some(path) => Config::load(path)?,
+            None => Config::default(),
+        };
+
+        let servers = &mut config.servers;
+        set_option(&mut servers.metrics_bind_addr, &self.metrics_bind_addr);
+        set(&mut servers.a.bind_addr, &self.rpc_bind_addr_a);
+        set(&mut servers.b.bind_addr, &self.rpc_bind_addr_b);
+        set(
+            &mut servers.b.trust_forwarded_for,
+            &self.rpc_b_trust_forwarded_for,
+        );
+        set_option(
+            &mut servers.b.api_key_path_prefix,
+            &self.rpc_b_api_key_path_prefix,
+        );
+        set(&mut servers.b.txpool, &self.rpc_b_txpool);
+        set(&mut servers.b.pin_latest, &self.rpc_b_pin_latest);
+        set(&mut servers.b.signer, &self.rpc_b_signer);
+        set_option(
+            &mut servers.b.logs_max_block_range,
+            &self.rpc_b_logs_max_block_range,
+        );
+        set_option(
+            &mut servers.b.logs_max_results,
+            &self.rpc_b_logs_max_results,
+        );
+
+        let backends = &mut config.backends;
+        set_option(&mut backends.eth_api_url, &self.eth_api_url);
+        set_option(&mut backends.eth_ws_url, &self.eth_ws_url);
+        set_option(&mut backends.engine_api_url, &self.engine_api_url);
+        set_option(&mut backends.routing_path, &self.backend_routing_path);
+        set(
+            &mut backends.poll_interval,
+            &self.backend_poll_interval.map(Into::into),
+        );
+        set(&mut backends.timeout, &self.backend_timeout.map(Into::into));
+        set(&mut backends.retries, &self.backend_retries);
+        set(
+            &mut backends.retry_backoff,
+            &self.backend_retry_backoff.map(Into::into),
+        );
+        set(
+            &mut backends.circuit_breaker_threshold,
+            &self.backend_circuit_breaker_threshold,
+        );
+        set(
+            &mut backends.circuit_breaker_cooldown,
+            &self.backend_circuit_breaker_cooldown.map(Into::into),
+        );
+        set_option(&mut backends.chain_id, &self.chain_id);
+        set_option(&mut backends.genesis_hash, &self.genesis_hash);
+        set(
+            &mut backends.engine_required_methods,
+            &self.engine_required_methods,
+        );
+        set(&mut backends.skip_preflight, &self.skip_preflight);
+
+        let auth = &mut config.auth;
+        set_option(
+            &mut auth.engine_jwt_secret_path,
+            &self.engine_api_secret_path,
+        );
+        set_option(&mut auth.api_keys_path, &self.rpc_b_api_keys_path);
+        set_option(&mut auth.signer_keystore_dir, &self.signer_keystore_dir);
+        set_option(&mut auth.signer_password_path, &self.signer_password_path);
+
+        let limits = &mut config.limits;
+        set_option(&mut limits.per_ip.write, &self.rpc_b_rate_limit_ip_write);
+        set_option(
+            &mut limits.per_ip.expensive,
+            &self.rpc_b_rate_limit_ip_expensive,
+        );
+        set_option(&mut limits.per_ip.other, &self.rpc_b_rate_limit_ip_other);
+        set_option(
+            &mut limits.per_api_key.write,
+            &self.rpc_b_rate_limit_api_key_write,
+        );
+        set_option(
+            &mut limits.per_api_key.expensive,
+            &self.rpc_b_rate_limit_api_key_expensive,
+        );
+        set_option(
+            &mut limits.per_api_key.other,
+            &self.rpc_b_rate_limit_api_key_other,
+        );
+        set_option(&mut limits.logs_chunk_size, &self.logs_chunk_size);
+        set(
+            &mut limits.logs_chunk_parallelism,
+            &self.logs_chunk_parallelism,
+        );
+        set(&mut limits.response_cache_size, &self.response_cache_size);
+        set(&mut limits.filter_ttl, &self.filter_ttl.map(Into::into));
+        set(&mut limits.max_filters, &self.max_filters);
+        set(
+            &mut limits.max_filters_per_client,
+            &self.max_filters_per_client,
+        );
+
+        let policies = &mut config.policies;
+        set_option(
+            &mut policies.admission_lists_path,
+            &self.admission_lists_path,
+        );
+        set_option(
+            &mut policies.admission_rules_path,
+            &self.admission_rules_path,
+        );
+        set(
+            &mut policies.admission_rules_poll_interval,
+            &self.admission_rules_poll_interval.map(Into::into),
+        );
+        set_option(&mut policies.pool_journal_path, &self.pool_journal_path);
+        set(
+            &mut policies.pool_maintenance_interval,
+            &self.pool_maintenance_interval.map(Into::into),
+        );
+        set(
+            &mut policies.pool_max_age,
+            &self.pool_max_age.map(Into::into),
+        );
+        set(&mut policies.gas_oracle_window, &self.gas_oracle_window);
+        set(&mut policies.gas_oracle_strategy, &self.gas_oracle_strategy);
+        set(&mut policies.gas_oracle_floor, &self.gas_oracle_floor);
+
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+fn load_signer(auth: &Auth) -> Result<Option<Signer>, AnyError> {
+    let Some(keystore_dir) = auth.signer_keystore_dir.as_deref() else {
+        return Ok(None);
+    };
+    let password = match auth.signer_password_path.as_deref() {
+        Some(path) => std::fs::read_to_string(path)?.trim_end().to_owned(),
+        None => String::new(),
+    };
+    let signer = Signer::load(keystore_dir, &password)?;
+    tracing::info!("Signing for {} accounts", signer.accounts().len());
+    Ok(Some(signer))
+}
+
+/// Overrides `setting` with the value of its flag, if given.
+fn set<T: Clone>(setting: &mut T, flag: &Option<T>) {
+    if let Some(value) = flag {
+        *setting = value.clone();
+    }
+}
+
+fn set_option<T: Clone>(setting: &mut Option<T>, flag: &Option<T>) {
+    if flag.is_some() {
+        *setting = flag.clone();
+    }
+}
+
+/// Keeps only the methods for which `keep` returns `true`.
+fn select_methods(
+    methods: impl Into<Methods>,
+    keep: impl Fn(&str) -> bool,
+) -> Result<Methods, AnyError> {
+    let methods = methods.into();
+    let mut selected = Methods::new();
+    for name in methods.method_names().filter(|name| keep(name)) {
+        let callback = methods.method(name).expect("listed by method_names").clone();
+        selected.verify_and_insert(name, callback)?;
+    }
+    Ok(selected)
 }
@@ -0,0 +1,398 @@
+use std::fmt::Display;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+use alloy_primitives::B256;
+use node::admission::AdmissionLists;
+use node::gas_oracle::Strategy;
+use node::quota::QuotaConfig;
+use node::rate_limit::{Budget, Budgets, RateLimitConfig};
+use node::routing::RoutingConfig;
+use serde::{Deserialize, Deserializer};
+
+use crate::AnyError;
+
+/// The settings of `node`, as read from the `--config` file.
+///
+/// Every key has a flag and an env-var counterpart, which take precedence over the file.
+/// Missing keys take their defaults. The routing, the API-keys and the admission lists may be
+/// given in place, in the format of their files. Only the `limits.per_ip` and
+/// `limits.per_api_key` budgets take effect when reloaded on SIGHUP; along with them the
+/// admission lists and rules and the API-keys are re-read from their files, or taken in place.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub servers: Servers,
+    pub backends: Backends,
+    pub auth: Auth,
+    pub limits: Limits,
+    pub policies: Policies,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Servers {
+    pub a: ServerA,
+    pub b: ServerB,
+    pub metrics_bind_addr: Option<SocketAddr>,
+}
+
+/// Server [A]: the Engine API and the full Eth API, for the rollup-node and internal tooling.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerA {
+    pub bind_addr: SocketAddr,
+}
+
+/// Server [B]: the public Eth API.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerB {
+    pub bind_addr: SocketAddr,
+    pub trust_forwarded_for: bool,
+    pub api_key_path_prefix: Option<String>,
+    #[serde(deserialize_with = "parsed")]
+    pub txpool: TxPoolExposure,
+    pub pin_latest: bool,
+    pub signer: bool,
+    pub logs_max_block_range: Option<u64>,
+    pub logs_max_results: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Backends {
+    pub eth_api_url: Option<String>,
+    pub eth_ws_url: Option<String>,
+    pub engine_api_url: Option<String>,
+    pub routing_path: Option<PathBuf>,
+    /// The routing, in place of `routing_path`.
+    pub routing: Option<RoutingConfig>,
+    #[serde(deserialize_with = "duration")]
+    pub poll_interval: Duration,
+    #[serde(deserialize_with = "duration")]
+    pub timeout: Duration,
+    pub retries: u32,
+    #[serde(deserialize_with = "duration")]
+    pub retry_backoff: Duration,
+    pub circuit_breaker_threshold: u32,
+    #[serde(deserialize_with = "duration")]
+    pub circuit_breaker_cooldown: Duration,
+    pub chain_id: Option<u64>,
+    #[serde(deserialize_with = "parsed_option")]
+    pub genesis_hash: Option<B256>,
+    pub engine_required_methods: Vec<String>,
+    pub skip_preflight: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Auth {
+    pub engine_jwt_secret_path: Option<PathBuf>,
+    pub api_keys_path: Option<PathBuf>,
+    /// The API-keys, in place of `api_keys_path`.
+    pub api_keys: Option<QuotaConfig>,
+    pub signer_keystore_dir: Option<PathBuf>,
+    pub signer_password_path: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Limits {
+    pub per_ip: RateLimits,
+    pub per_api_key: RateLimits,
+    pub logs_chunk_size: Option<u64>,
+    pub logs_chunk_parallelism: usize,
+    pub response_cache_size: usize,
+    #[serde(deserialize_with = "duration")]
+    pub filter_ttl: Duration,
+    pub max_filters: usize,
+    pub max_filters_per_client: usize,
+}
+
+/// Rate-limits on server [B], as `RATE[:BURST]` with `RATE` in requests per second.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RateLimits {
+    #[serde(deserialize_with = "parsed_option")]
+    pub write: Option<Budget>,
+    #[serde(deserialize_with = "parsed_option")]
+    pub expensive: Option<Budget>,
+    #[serde(deserialize_with = "parsed_option")]
+    pub other: Option<Budget>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Policies {
+    pub admission_lists_path: Option<PathBuf>,
+    /// The admission lists, in place of `admission_lists_path`.
+    pub admission_lists: Option<AdmissionLists>,
+    pub admission_rules_path: Option<PathBuf>,
+    #[serde(deserialize_with = "duration")]
+    pub admission_rules_poll_interval: Duration,
+    pub pool_journal_path: Option<PathBuf>,
+    #[serde(deserialize_with = "duration")]
+    pub pool_maintenance_interval: Duration,
+    #[serde(deserialize_with = "duration")]
+    pub pool_max_age: Duration,
+    pub gas_oracle_window: usize,
+    #[serde(deserialize_with = "parsed")]
+    pub gas_oracle_strategy: Strategy,
+    pub gas_oracle_floor: u128,
+}
+
+/// Which of the `txpool_*` methods server [B] serves.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TxPoolExposure {
+    Full,
+    Status,
+    None,
+}
+
+impl Config {
+    /// Reads the file at `path`; a malformed file is reported along with the offending key.
+    pub fn load(path: &Path) -> Result<Self, AnyError> {
+        let text =
+            std::fs::read_to_string(path).map_err(|reason| format!("{:?}: {}", path, reason))?;
+        let config =
+            serde_path_to_error::deserialize(toml::Deserializer::new(&text)).map_err(|reason| {
+                match reason.path().to_string().as_str() {
+                    "." => format!("{:?}: {}", path, reason.inner()),
+                    key => format!("{:?}: `{}`: {}", path, key, reason.inner().message()),
+                }
+            })?;
+        Ok(config)
+    }
+
+    /// Checks the settings that are only valid together, or within some range.
+    pub fn validate(&self) -> Result<(), AnyError> {
+        let required = [
+            ("backends.eth_api_url", self.backends.eth_api_url.is_some()),
+            (
+                "backends.engine_api_url",
+                self.backends.engine_api_url.is_some(),
+            ),
+            (
+                "auth.engine_jwt_secret_path",
+                self.auth.engine_jwt_secret_path.is_some(),
+            ),
+        ];
+        if let Some((key, _)) = required.iter().find(|(_, is_set)| !is_set) {
+            return Err(invalid(key, "is required"));
+        }
+        let in_place = [
+            (
+                "backends.routing",
+                self.backends.routing.is_some() && self.backends.routing_path.is_some(),
+                "backends.routing_path",
+            ),
+            (
+                "auth.api_keys",
+                self.auth.api_keys.is_some() && self.auth.api_keys_path.is_some(),
+                "auth.api_keys_path",
+            ),
+            (
+                "policies.admission_lists",
+                self.policies.admission_lists.is_some()
+                    && self.policies.admission_lists_path.is_some(),
+                "policies.admission_lists_path",
+            ),
+        ];
+        if let Some((key, _, path_key)) = in_place.iter().find(|(_, both, _)| *both) {
+            return Err(invalid(key, &format!("conflicts with `{}`", path_key)));
+        }
+        if self.policies.gas_oracle_window == 0 {
+            return Err(invalid("policies.gas_oracle_window", "must be at least 1"));
+        }
+        if self.limits.logs_chunk_size == Some(0) {
+            return Err(invalid("limits.logs_chunk_size", "must be at least 1"));
+        }
+        if self.limits.logs_chunk_parallelism == 0 {
+            return Err(invalid(
+                "limits.logs_chunk_parallelism",
+                "must be at least 1",
+            ));
+        }
+        if self.servers.b.signer && self.auth.signer_keystore_dir.is_none() {
+            return Err(invalid(
+                "servers.b.signer",
+                "requires `auth.signer_keystore_dir`",
+            ));
+        }
+        Ok(())
+    }
+
+    /// The sections that differ in `reloaded` other than by what a reload applies, and only
+    /// take effect on restart.
+    pub fn restart_required(&self, reloaded: &Self) -> Vec<&'static str> {
+        let mut reloaded = reloaded.clone();
+        reloaded.limits.per_ip = self.limits.per_ip.clone();
+        reloaded.limits.per_api_key = self.limits.per_api_key.clone();
+        reloaded.auth.api_keys = self.auth.api_keys.clone();
+        reloaded.policies.admission_lists = self.policies.admission_lists.clone();
+        [
+            ("servers", self.servers != reloaded.servers),
+            ("backends", self.backends != reloaded.backends),
+            ("auth", self.auth != reloaded.auth),
+            ("limits", self.limits != reloaded.limits),
+            ("policies", self.policies != reloaded.policies),
+        ]
+        .into_iter()
+        .filter_map(|(section, changed)| changed.then_some(section))
+        .collect()
+    }
+}
+
+impl Limits {
+    pub fn rate_limit_config(&self) -> RateLimitConfig {
+        RateLimitConfig {
+            per_ip: self.per_ip.budgets(),
+            per_api_key: self.per_api_key.budgets(),
+        }
+    }
+}
+
+impl RateLimits {
+    fn budgets(&self) -> Budgets {
+        Budgets {
+            write: self.write,
+            expensive: self.expensive,
+            other: self.other,
+        }
+    }
+}
+
+impl Default for ServerA {
+    fn default() -> Self {
+        Self {
+            bind_addr: ([0, 0, 0, 0], 8551).into(),
+        }
+    }
+}
+
+impl Default for ServerB {
+    fn default() -> Self {
+        Self {
+            bind_addr: ([0, 0, 0, 0], 8545).into(),
+            trust_forwarded_for: false,
+            api_key_path_prefix: None,
+            txpool: TxPoolExposure::Status,
+            pin_latest: false,
+            signer: false,
+            logs_max_block_range: None,
+            logs_max_results: None,
+        }
+    }
+}
+
+impl Default for Backends {
+    fn default() -> Self {
+        Self {
+            eth_api_url: None,
+            eth_ws_url: None,
+            engine_api_url: None,
+            routing_path: None,
+            routing: None,
+            poll_interval: Duration::from_secs(1),
+            timeout: Duration::from_secs(30),
+            retries: 2,
+            retry_backoff: Duration::from_millis(100),
+            circuit_breaker_threshold: 5,
+            circuit_breaker_cooldown: Duration::from_secs(10),
+            chain_id: None,
+            genesis_hash: None,
+            engine_required_methods: [
+                "engine_newPayloadV2",
+                "engine_forkchoiceUpdatedV2",
+                "engine_getPayloadV2",
+            ]
+            .map(String::from)
+            .to_vec(),
+            skip_preflight: false,
+        }
+    }
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            per_ip: Default::default(),
+            per_api_key: Default::default(),
+            logs_chunk_size: None,
+            logs_chunk_parallelism: 4,
+            response_cache_size: 10_000,
+            filter_ttl: Duration::from_secs(5 * 60),
+            max_filters: 10_000,
+            max_filters_per_client: 100,
+        }
+    }
+}
+
+impl Default for Policies {
+    fn default() -> Self {
+        Self {
+            admission_lists_path: None,
+            admission_lists: None,
+            admission_rules_path: None,
+            admission_rules_poll_interval: Duration::from_secs(5),
+            pool_journal_path: None,
+            pool_maintenance_interval: Duration::from_secs(10),
+            pool_max_age: Duration::from_secs(3 * 60 * 60),
+            gas_oracle_window: 20,
+            gas_oracle_strategy: Strategy::Percentile(60.0),
+            gas_oracle_floor: 0,
+        }
+    }
+}
+
+impl FromStr for TxPoolExposure {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "full" => Ok(Self::Full),
+            "status" => Ok(Self::Status),
+            "none" => Ok(Self::None),
+            _ => Err(format!(
+                "expected one of `full`, `status`, `none`; got {:?}",
+                s
+            )),
+        }
+    }
+}
+
+fn invalid(key: &str, reason: &str) -> AnyError {
+    format!("invalid configuration: `{}` {}", key, reason).into()
+}
+
+/// Takes a value the way its flag does: as a string, parsed.
+fn parsed<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    String::deserialize(deserializer)?
+        .parse()
+        .map_err(serde::de::Error::custom)
+}
+
+fn parsed_option<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    parsed(deserializer).map(Some)
+}
+
+/// A duration such as `"1m 30s"` or `"100ms"`.
+fn duration<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    parsed::<_, humantime::Duration>(deserializer).map(Into::into)
+}
@@ -1,6 +1,7 @@
 use structopt::StructOpt;
 
 pub mod commands;
+pub mod config;
 
 pub type AnyError = Box<dyn std::error::Error + Send + Sync + 'static>;
 
@@ -14,6 +15,7 @@ pub struct Cli {
 enum Command {
     Init(commands::Init),
     Node(commands::Node),
+    CheckTx(commands::CheckTx),
 }
 
 impl Cli {
@@ -21,6 +23,7 @@ impl Cli {
         match &self.command {
             Command::Init(inner) => inner.run(self).await,
             Command::Node(inner) => inner.run(self).await,
+            Command::CheckTx(inner) => inner.run(self).await,
         }
     }
 }